@@ -0,0 +1,156 @@
+/*
+Large seven-segment characters drawn with block characters, so the time can be read from across the room
+*/
+
+// The segments of each character, a to g being bits 0 to 6
+const A: u8 = 1;
+const B: u8 = 1 << 1;
+const C: u8 = 1 << 2;
+const D: u8 = 1 << 3;
+const E: u8 = 1 << 4;
+const F: u8 = 1 << 5;
+const G: u8 = 1 << 6;
+
+/*
+The text as rows of the largest characters that fit in the area, None if even the smallest do not fit or there is a character without segments
+*/
+pub fn render(text: &str, width: u16, height: u16) -> Option<Vec<String>> {
+    let glyphs: Vec<Glyph> = text.chars().map(glyph).collect::<Option<_>>()?;
+    let scale = (1..)
+        .take_while(|&scale| {
+            let size = Size::new(scale);
+            size.height <= height as usize && size.text_width(&glyphs) <= width as usize
+        })
+        .last()?;
+    let size = Size::new(scale);
+
+    let rows = (0..size.height)
+        .map(|row| {
+            let mut line = String::new();
+            for (index, glyph) in glyphs.iter().enumerate() {
+                if index > 0 {
+                    line.push_str(&" ".repeat(size.gap));
+                }
+                for column in 0..size.width(glyph) {
+                    line.push(if size.filled(glyph, row, column) {
+                        '█'
+                    } else {
+                        ' '
+                    });
+                }
+            }
+            line
+        })
+        .collect();
+    Some(rows)
+}
+
+enum Glyph {
+    Segments(u8),
+    Colon,
+    Point,
+    Plus,
+}
+
+fn glyph(c: char) -> Option<Glyph> {
+    let segments = match c {
+        '0' => A | B | C | D | E | F,
+        '1' => B | C,
+        '2' => A | B | D | E | G,
+        '3' => A | B | C | D | G,
+        '4' => B | C | F | G,
+        '5' => A | C | D | F | G,
+        '6' => A | C | D | E | F | G,
+        '7' => A | B | C,
+        '8' => A | B | C | D | E | F | G,
+        '9' => A | B | C | D | F | G,
+        '-' => G,
+        // Enough to spell DNF and the brackets around its time
+        'D' => B | C | D | E | G,
+        'N' => C | E | G,
+        'F' => A | E | F | G,
+        '(' => A | D | E | F,
+        ')' => A | B | C | D,
+        ':' => return Some(Glyph::Colon),
+        '.' => return Some(Glyph::Point),
+        '+' => return Some(Glyph::Plus),
+        _ => return None,
+    };
+    Some(Glyph::Segments(segments))
+}
+
+/*
+Terminal cells are about twice as tall as they are wide, so vertical strokes are twice as thick as horizontal ones
+*/
+struct Size {
+    height: usize,
+    digit_width: usize,
+    // Rows in a horizontal stroke and columns in a vertical one
+    thickness: usize,
+    stroke: usize,
+    gap: usize,
+}
+
+impl Size {
+    fn new(scale: usize) -> Size {
+        let thickness = scale.div_ceil(2);
+        Size {
+            height: 4 * scale + 1,
+            digit_width: 4 * scale + 2,
+            thickness,
+            stroke: 2 * thickness,
+            gap: 2 * thickness,
+        }
+    }
+
+    fn width(&self, glyph: &Glyph) -> usize {
+        match glyph {
+            Glyph::Colon | Glyph::Point => self.stroke,
+            _ => self.digit_width,
+        }
+    }
+
+    fn text_width(&self, glyphs: &[Glyph]) -> usize {
+        let widths: usize = glyphs.iter().map(|glyph| self.width(glyph)).sum();
+        widths + self.gap * glyphs.len().saturating_sub(1)
+    }
+
+    fn filled(&self, glyph: &Glyph, row: usize, column: usize) -> bool {
+        let middle = self.height / 2;
+        let top = row < self.thickness;
+        let bottom = row >= self.height - self.thickness;
+        let centre_row =
+            row + self.thickness / 2 >= middle && row < middle + self.thickness.div_ceil(2);
+        match glyph {
+            Glyph::Segments(segments) => {
+                let left = column < self.stroke;
+                let right = column >= self.digit_width - self.stroke;
+                let upper = row <= middle;
+                let lower = row >= middle;
+                let lit = |segment: u8| segments & segment != 0;
+                (lit(A) && top)
+                    || (lit(B) && right && upper)
+                    || (lit(C) && right && lower)
+                    || (lit(D) && bottom)
+                    || (lit(E) && left && lower)
+                    || (lit(F) && left && upper)
+                    || (lit(G) && centre_row)
+            }
+            // Dots halfway up each half
+            Glyph::Colon => {
+                let quarter = self.height / 4;
+                (row >= quarter && row < quarter + self.thickness)
+                    || (row + self.thickness > 3 * quarter && row <= 3 * quarter)
+            }
+            Glyph::Point => bottom,
+            Glyph::Plus => {
+                let centre_column = self.digit_width / 2;
+                let vertical = column + self.stroke / 2 >= centre_column
+                    && column < centre_column + self.stroke.div_ceil(2)
+                    && row >= self.height / 4
+                    && row <= 3 * self.height / 4;
+                centre_row || vertical
+            }
+        }
+    }
+}
@@ -0,0 +1,265 @@
+/*
+Settings for the timer and display, from `config.toml` in the XDG config directory
+
+Anything left out of the file keeps its default, and the file is only written when a setting is changed in the settings screen.
+*/
+
+use crate::{
+    method::{self, Methods},
+    storage,
+    theme::{self, Theme, Themes},
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, io, time::Duration};
+
+const CONFIG_FILE: &str = "config.toml";
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    // How often the screen is redrawn
+    pub tick_rate_ms: u64,
+    // How long the timer key has to be held before releasing it starts the solve
+    pub hold_time_ms: u64,
+    // How long without a repeat before a held key counts as released, for terminals without release events
+    pub release_threshold_ms: u64,
+    pub inspection: bool,
+    pub inspection_seconds: u64,
+    // Decimal places shown in times
+    pub precision: u64,
+    // The method whose phases solves are split into
+    pub method: String,
+    pub theme: String,
+    #[serde(skip_serializing_if = "Methods::is_empty")]
+    pub methods: Methods,
+    #[serde(skip_serializing_if = "Themes::is_empty")]
+    pub themes: Themes,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            tick_rate_ms: 10,
+            hold_time_ms: 300,
+            release_threshold_ms: 600,
+            inspection: true,
+            inspection_seconds: 15,
+            precision: 2,
+            method: "none".to_string(),
+            theme: "dark".to_string(),
+            methods: Methods::new(),
+            themes: Themes::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Setting {
+    TickRate,
+    HoldTime,
+    ReleaseThreshold,
+    Inspection,
+    InspectionTime,
+    Precision,
+    Method,
+    Theme,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 8] = [
+        Setting::Inspection,
+        Setting::InspectionTime,
+        Setting::HoldTime,
+        Setting::Method,
+        Setting::Precision,
+        Setting::Theme,
+        Setting::TickRate,
+        Setting::ReleaseThreshold,
+    ];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Setting::TickRate => "Tick rate (ms)",
+            Setting::HoldTime => "Hold time (ms)",
+            Setting::ReleaseThreshold => "Release threshold (ms)",
+            Setting::Inspection => "Inspection",
+            Setting::InspectionTime => "Inspection time (s)",
+            Setting::Precision => "Decimal places",
+            Setting::Method => "Split method",
+            Setting::Theme => "Theme",
+        }
+    }
+
+    // The name in the config file
+    fn name(self) -> &'static str {
+        match self {
+            Setting::TickRate => "tick_rate_ms",
+            Setting::HoldTime => "hold_time_ms",
+            Setting::ReleaseThreshold => "release_threshold_ms",
+            Setting::Inspection => "inspection",
+            Setting::InspectionTime => "inspection_seconds",
+            Setting::Precision => "precision",
+            Setting::Method => "method",
+            Setting::Theme => "theme",
+        }
+    }
+
+    pub fn is_number(self) -> bool {
+        self.range().is_some()
+    }
+
+    /*
+    The values a number can have, None for settings that are chosen from a few
+    */
+    fn range(self) -> Option<(u64, u64)> {
+        match self {
+            Setting::TickRate => Some((1, 100)),
+            Setting::HoldTime => Some((0, 2000)),
+            // Longer than the delay before a terminal starts repeating a key, usually 250 to 660 milliseconds, or holding the key would count as releasing it
+            Setting::ReleaseThreshold => Some((300, 2000)),
+            Setting::Inspection | Setting::Method | Setting::Theme => None,
+            Setting::InspectionTime => Some((1, 60)),
+            Setting::Precision => Some((0, 3)),
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> io::Result<Config> {
+        let path = storage::config_dir()?.join(CONFIG_FILE);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(err) => return Err(err),
+        };
+        let config: Config = toml::from_str(&contents)
+            .map_err(|err| invalid_data(format!("{}: {}", path.display(), err)))?;
+        config
+            .validate()
+            .map_err(|err| invalid_data(format!("{}: {}", path.display(), err)))?;
+        Ok(config)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let dir = storage::config_dir()?;
+        fs::create_dir_all(&dir)?;
+        let contents = toml::to_string(self).map_err(|err| invalid_data(err.to_string()))?;
+        storage::write_atomic(&dir.join(CONFIG_FILE), contents.as_bytes())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for setting in Setting::ALL {
+            if let (Some(value), Some((min, max))) = (self.number(setting), setting.range()) {
+                if value < min || value > max {
+                    return Err(format!("{} should be from {min} to {max}", setting.name()));
+                }
+            }
+        }
+        for name in theme::names(&self.themes) {
+            Theme::named(name, &self.themes)?;
+        }
+        Theme::named(&self.theme, &self.themes)?;
+        for name in method::names(&self.methods) {
+            method::phases(name, &self.methods)?;
+        }
+        method::phases(&self.method, &self.methods)?;
+        Ok(())
+    }
+
+    fn number(&self, setting: Setting) -> Option<u64> {
+        match setting {
+            Setting::TickRate => Some(self.tick_rate_ms),
+            Setting::HoldTime => Some(self.hold_time_ms),
+            Setting::ReleaseThreshold => Some(self.release_threshold_ms),
+            Setting::Inspection | Setting::Method | Setting::Theme => None,
+            Setting::InspectionTime => Some(self.inspection_seconds),
+            Setting::Precision => Some(self.precision),
+        }
+    }
+
+    pub fn value(&self, setting: Setting) -> String {
+        match setting {
+            Setting::Inspection if self.inspection => "On".to_string(),
+            Setting::Inspection => "Off".to_string(),
+            Setting::Method => self.method.clone(),
+            Setting::Theme => self.theme.clone(),
+            _ => self.number(setting).unwrap_or_default().to_string(),
+        }
+    }
+
+    /*
+    Switches a setting that is on or off, or moves on to the next method or theme
+    */
+    pub fn toggle(&mut self, setting: Setting) {
+        match setting {
+            Setting::Inspection => self.inspection = !self.inspection,
+            Setting::Method => self.method = next(&method::names(&self.methods), &self.method),
+            Setting::Theme => self.theme = next(&theme::names(&self.themes), &self.theme),
+            _ => {}
+        }
+    }
+
+    pub fn phases(&self) -> Vec<String> {
+        // Checked when the config was loaded
+        method::phases(&self.method, &self.methods).expect("The method should have been validated")
+    }
+
+    pub fn theme(&self) -> Theme {
+        // Checked when the config was loaded
+        Theme::named(&self.theme, &self.themes).expect("The theme should have been validated")
+    }
+
+    /*
+    Sets a number from what was typed, leaving the config unchanged if it is not allowed
+    */
+    pub fn set(&mut self, setting: Setting, text: &str) -> Result<(), String> {
+        let field = match setting {
+            Setting::TickRate => &mut self.tick_rate_ms,
+            Setting::HoldTime => &mut self.hold_time_ms,
+            Setting::ReleaseThreshold => &mut self.release_threshold_ms,
+            Setting::InspectionTime => &mut self.inspection_seconds,
+            Setting::Precision => &mut self.precision,
+            Setting::Inspection | Setting::Method | Setting::Theme => {
+                return Err(format!("{} is not a number", setting.description()))
+            }
+        };
+        let (min, max) = setting.range().unwrap_or((0, u64::MAX));
+        let value = text
+            .trim()
+            .parse::<u64>()
+            .ok()
+            .filter(|value| (min..=max).contains(value))
+            .ok_or_else(|| format!("Should be a number from {min} to {max}"))?;
+        *field = value;
+        Ok(())
+    }
+
+    pub fn tick_rate(&self) -> Duration {
+        Duration::from_millis(self.tick_rate_ms)
+    }
+
+    pub fn hold_time(&self) -> Duration {
+        Duration::from_millis(self.hold_time_ms)
+    }
+
+    pub fn release_threshold(&self) -> Duration {
+        Duration::from_millis(self.release_threshold_ms)
+    }
+
+    pub fn inspection_time(&self) -> Duration {
+        Duration::from_secs(self.inspection_seconds)
+    }
+}
+
+/*
+The name after the current one, going back to the first after the last
+*/
+fn next(names: &[&str], current: &str) -> String {
+    let index = names.iter().position(|&name| name == current);
+    let next = index.map_or(0, |index| (index + 1) % names.len());
+    names[next].to_string()
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
@@ -0,0 +1,349 @@
+/*
+The sticker level of an NxN cube
+*/
+
+use super::{parse_algorithm, CubieCube, Face, Move, Vector};
+
+// The stickers of each corner and edge position of a 3x3, clockwise for corners, in the order of the cubie level
+const CORNER_FACELETS: [[usize; 3]; 8] = [
+    [8, 9, 20],
+    [6, 18, 38],
+    [0, 36, 47],
+    [2, 45, 11],
+    [29, 26, 15],
+    [27, 44, 24],
+    [33, 53, 42],
+    [35, 17, 51],
+];
+const EDGE_FACELETS: [[usize; 2]; 12] = [
+    [5, 10],
+    [7, 19],
+    [3, 37],
+    [1, 46],
+    [32, 16],
+    [28, 25],
+    [30, 43],
+    [34, 52],
+    [23, 12],
+    [21, 41],
+    [50, 39],
+    [48, 14],
+];
+// The colours of each corner and edge piece, in the same order as their stickers
+const CORNER_COLOURS: [[Face; 3]; 8] = [
+    [Face::U, Face::R, Face::F],
+    [Face::U, Face::F, Face::L],
+    [Face::U, Face::L, Face::B],
+    [Face::U, Face::B, Face::R],
+    [Face::D, Face::F, Face::R],
+    [Face::D, Face::L, Face::F],
+    [Face::D, Face::B, Face::L],
+    [Face::D, Face::R, Face::B],
+];
+const EDGE_COLOURS: [[Face; 2]; 12] = [
+    [Face::U, Face::R],
+    [Face::U, Face::F],
+    [Face::U, Face::L],
+    [Face::U, Face::B],
+    [Face::D, Face::R],
+    [Face::D, Face::F],
+    [Face::D, Face::L],
+    [Face::D, Face::B],
+    [Face::F, Face::R],
+    [Face::F, Face::L],
+    [Face::B, Face::L],
+    [Face::B, Face::R],
+];
+
+/*
+The colour of every sticker, face by face in the order U R F D L B and row by row within each face as it appears in the unfolded net
+*/
+#[derive(Clone, PartialEq, Debug)]
+pub struct FaceletCube {
+    size: usize,
+    facelets: Vec<Face>,
+}
+
+impl FaceletCube {
+    pub fn solved(size: usize) -> FaceletCube {
+        let facelets = Face::ALL
+            .iter()
+            .flat_map(|&face| std::iter::repeat_n(face, size * size))
+            .collect();
+        FaceletCube { size, facelets }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /*
+    The colour of a sticker, with rows and columns as the face appears in the unfolded net
+    */
+    pub fn facelet(&self, face: Face, row: usize, col: usize) -> Face {
+        self.facelets[(face as usize * self.size + row) * self.size + col]
+    }
+
+    /*
+    Solved in any orientation, so every face is a single colour
+    */
+    pub fn is_solved(&self) -> bool {
+        self.facelets
+            .chunks(self.size * self.size)
+            .all(|face| face.iter().all(|&colour| colour == face[0]))
+    }
+
+    pub fn apply_algorithm(&mut self, algorithm: &str) -> Result<(), String> {
+        for m in parse_algorithm(algorithm, self.size)? {
+            self.apply_move(&m);
+        }
+        Ok(())
+    }
+
+    pub fn apply_move(&mut self, m: &Move) {
+        let mut facelets = self.facelets.clone();
+        for (index, destination) in self.move_permutation(m).into_iter().enumerate() {
+            facelets[destination] = self.facelets[index];
+        }
+        self.facelets = facelets;
+    }
+
+    /*
+    Where each sticker ends up after the move
+    */
+    pub fn move_permutation(&self, m: &Move) -> Vec<usize> {
+        let axis = m.face.normal();
+        (0..self.facelets.len())
+            .map(|index| {
+                let (mut position, mut normal) = self.sticker(index);
+                if !m.layers.contains(&self.depth(position, axis)) {
+                    return index;
+                }
+                for _ in 0..m.quarter_turns {
+                    position = turn_clockwise(position, axis);
+                    normal = turn_clockwise(normal, axis);
+                }
+                self.index(position, normal)
+            })
+            .collect()
+    }
+
+    pub fn facelets(&self) -> &[Face] {
+        &self.facelets
+    }
+
+    /*
+    Only for 3x3s with their centres in the standard orientation, None if the stickers do not make up real pieces
+    */
+    pub fn to_cubie(&self) -> Option<CubieCube> {
+        if self.size != 3 {
+            return None;
+        }
+        let mut cube = CubieCube::SOLVED;
+        for (position, facelets) in CORNER_FACELETS.iter().enumerate() {
+            let colours = facelets.map(|facelet| self.facelets[facelet]);
+            // The twist is how far round the U or D sticker is
+            let twist = colours
+                .iter()
+                .position(|&colour| colour == Face::U || colour == Face::D)?;
+            let colours = [0, 1, 2].map(|k| colours[(twist + k) % 3]);
+            cube.cp[position] = CORNER_COLOURS.iter().position(|&piece| piece == colours)? as u8;
+            cube.co[position] = twist as u8;
+        }
+        for (position, facelets) in EDGE_FACELETS.iter().enumerate() {
+            let colours = facelets.map(|facelet| self.facelets[facelet]);
+            let flipped = [colours[1], colours[0]];
+            let (piece, flip) =
+                EDGE_COLOURS
+                    .iter()
+                    .enumerate()
+                    .find_map(|(piece, &piece_colours)| {
+                        if piece_colours == colours {
+                            Some((piece, 0))
+                        } else if piece_colours == flipped {
+                            Some((piece, 1))
+                        } else {
+                            None
+                        }
+                    })?;
+            cube.ep[position] = piece as u8;
+            cube.eo[position] = flip;
+        }
+        Some(cube)
+    }
+
+    /*
+    The position of a sticker and the direction it faces, in units of half a piece from the centre of the cube
+    */
+    fn sticker(&self, index: usize) -> (Vector, Vector) {
+        let n = self.size as i32;
+        let face = Face::ALL[index / (self.size * self.size)];
+        let row = (index / self.size % self.size) as i32;
+        let col = (index % self.size) as i32;
+        let normal = face.normal();
+        let position = [0, 1, 2].map(|i| {
+            normal[i] * n
+                + face.right()[i] * (2 * col - (n - 1))
+                + face.down()[i] * (2 * row - (n - 1))
+        });
+        (position, normal)
+    }
+
+    fn index(&self, position: Vector, normal: Vector) -> usize {
+        let n = self.size as i32;
+        let face = Face::from_normal(normal);
+        let row = (dot(position, face.down()) + n - 1) / 2;
+        let col = (dot(position, face.right()) + n - 1) / 2;
+        face as usize * self.size * self.size + (row * n + col) as usize
+    }
+
+    /*
+    Which layer a sticker belongs to, counting from 1 at the face the axis points out of
+    */
+    fn depth(&self, position: Vector, axis: Vector) -> usize {
+        let n = self.size as i32;
+        // Stickers on the face itself belong to the outer layer
+        let layer = dot(position, axis).clamp(-(n - 1), n - 1);
+        ((n - 1 - layer) / 2 + 1) as usize
+    }
+}
+
+impl From<&CubieCube> for FaceletCube {
+    fn from(cube: &CubieCube) -> FaceletCube {
+        let mut facelets = FaceletCube::solved(3);
+        for (position, stickers) in CORNER_FACELETS.iter().enumerate() {
+            let piece = CORNER_COLOURS[cube.cp[position] as usize];
+            let twist = cube.co[position] as usize;
+            for (k, &colour) in piece.iter().enumerate() {
+                facelets.facelets[stickers[(k + twist) % 3]] = colour;
+            }
+        }
+        for (position, stickers) in EDGE_FACELETS.iter().enumerate() {
+            let piece = EDGE_COLOURS[cube.ep[position] as usize];
+            let flip = cube.eo[position] as usize;
+            for (k, &colour) in piece.iter().enumerate() {
+                facelets.facelets[stickers[(k + flip) % 2]] = colour;
+            }
+        }
+        facelets
+    }
+}
+
+fn dot(a: Vector, b: Vector) -> i32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/*
+A clockwise quarter turn about the axis as seen from the end it points to
+*/
+fn turn_clockwise(vector: Vector, axis: Vector) -> Vector {
+    let along = dot(vector, axis);
+    let cross = [
+        axis[1] * vector[2] - axis[2] * vector[1],
+        axis[2] * vector[0] - axis[0] * vector[2],
+        axis[0] * vector[1] - axis[1] * vector[0],
+    ];
+    [0, 1, 2].map(|i| axis[i] * along - cross[i])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::FACE_TURNS;
+
+    // Some moves to start from so that comparisons are not between solved cubes
+    const SETUP: &str = "R U2 F' L D' B2 U R' F2";
+
+    fn after(size: usize, algorithm: &str) -> FaceletCube {
+        let mut cube = FaceletCube::solved(size);
+        cube.apply_algorithm(algorithm).unwrap();
+        cube
+    }
+
+    #[test]
+    fn sexy_move_six_times_is_solved() {
+        for size in 2..=7 {
+            let mut cube = FaceletCube::solved(size);
+            for time in 1..=6 {
+                cube.apply_algorithm("R U R' U'").unwrap();
+                assert_eq!(cube.is_solved(), time == 6, "{size}x{size} after {time}");
+            }
+        }
+    }
+
+    #[test]
+    fn wide_moves_and_rotations_match_their_slices() {
+        assert_eq!(
+            after(3, &format!("{SETUP} r")),
+            after(3, &format!("{SETUP} R M'"))
+        );
+        assert_eq!(
+            after(4, &format!("{SETUP} r")),
+            after(4, &format!("{SETUP} R 2R"))
+        );
+        assert_eq!(
+            after(5, &format!("{SETUP} 3Rw'")),
+            after(5, &format!("{SETUP} R' 2R' 3R'"))
+        );
+        for size in 3..=7 {
+            for (rotation, slices) in [("x", "R M' L'"), ("y", "U E' D'"), ("z", "F S B'")] {
+                assert_eq!(
+                    after(size, &format!("{SETUP} {rotation}")),
+                    after(size, &format!("{SETUP} {slices}")),
+                    "{rotation} on {size}x{size}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn is_solved_in_any_orientation() {
+        assert!(FaceletCube::solved(3).is_solved());
+        assert!(after(3, "x y2 z'").is_solved());
+        assert!(after(4, "R U R' U' U R U' R'").is_solved());
+        assert!(!after(3, "R").is_solved());
+        assert!(!after(4, "2R").is_solved());
+        assert!(!after(3, "M2").is_solved());
+    }
+
+    #[test]
+    fn face_turns_match_the_cubie_level() {
+        for (face, turn) in Face::ALL.iter().zip(FACE_TURNS) {
+            assert_eq!(after(3, &format!("{face:?}")).to_cubie(), Some(turn));
+        }
+        let cubie = [0, 1, 4, 2, 5, 0, 3]
+            .iter()
+            .fold(CubieCube::SOLVED, |cube, &face| {
+                cube.multiply(&FACE_TURNS[face])
+            });
+        assert_eq!(after(3, "U R L F B U D").to_cubie(), Some(cubie));
+    }
+
+    #[test]
+    fn cubie_round_trips() {
+        let cube = after(3, SETUP);
+        let cubie = cube.to_cubie().unwrap();
+        assert_eq!(FaceletCube::from(&cubie), cube);
+        assert_eq!(FaceletCube::from(&cubie).to_cubie(), Some(cubie));
+        assert_eq!(FaceletCube::solved(3).to_cubie(), Some(CubieCube::SOLVED));
+    }
+
+    #[test]
+    fn to_cubie_needs_real_pieces() {
+        assert_eq!(FaceletCube::solved(4).to_cubie(), None);
+        // Two stickers of a corner swapped, which no piece has
+        let mut cube = FaceletCube::solved(3);
+        cube.facelets.swap(8, 9);
+        assert_eq!(cube.to_cubie(), None);
+    }
+
+    #[test]
+    fn invalid_moves_leave_the_cube_unchanged() {
+        let mut cube = FaceletCube::solved(3);
+        assert_eq!(
+            cube.apply_algorithm("R U Q"),
+            Err("Invalid move: Q".to_string())
+        );
+        assert!(cube.is_solved());
+    }
+}
@@ -0,0 +1,84 @@
+/*
+A model of NxN cubes
+
+The facelet level stores the colour of every sticker and can apply any move, the cubie level stores which piece is where on a 3x3 and is what solvers work with.
+*/
+
+mod cubie;
+mod facelet;
+mod moves;
+
+pub use cubie::{CubieCube, FACE_TURNS};
+pub use facelet::FaceletCube;
+pub use moves::{parse_algorithm, Move};
+
+/*
+The faces in the order of the standard facelet string, which is also the colour of their centres on a solved cube
+*/
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Face {
+    U,
+    R,
+    F,
+    D,
+    L,
+    B,
+}
+
+// A direction in space, with x to the right, y up and z towards the front
+type Vector = [i32; 3];
+
+impl Face {
+    pub const ALL: [Face; 6] = [Face::U, Face::R, Face::F, Face::D, Face::L, Face::B];
+
+    fn from_letter(letter: char) -> Option<Face> {
+        match letter {
+            'U' => Some(Face::U),
+            'R' => Some(Face::R),
+            'F' => Some(Face::F),
+            'D' => Some(Face::D),
+            'L' => Some(Face::L),
+            'B' => Some(Face::B),
+            _ => None,
+        }
+    }
+
+    // Pointing out of the face
+    fn normal(self) -> Vector {
+        match self {
+            Face::U => [0, 1, 0],
+            Face::R => [1, 0, 0],
+            Face::F => [0, 0, 1],
+            Face::D => [0, -1, 0],
+            Face::L => [-1, 0, 0],
+            Face::B => [0, 0, -1],
+        }
+    }
+
+    /*
+    The directions of the columns and rows of the face as it appears in the unfolded net, where U has B at the top, D has F at the top and the others have U at the top
+    */
+    fn right(self) -> Vector {
+        match self {
+            Face::U | Face::F | Face::D => [1, 0, 0],
+            Face::R => [0, 0, -1],
+            Face::L => [0, 0, 1],
+            Face::B => [-1, 0, 0],
+        }
+    }
+
+    fn down(self) -> Vector {
+        match self {
+            Face::U => [0, 0, 1],
+            Face::D => [0, 0, -1],
+            _ => [0, -1, 0],
+        }
+    }
+
+    fn from_normal(normal: Vector) -> Face {
+        Face::ALL
+            .into_iter()
+            .find(|face| face.normal() == normal)
+            .unwrap_or(Face::U)
+    }
+}
@@ -0,0 +1,138 @@
+/*
+Moves in WCA/SiGN notation
+*/
+
+use super::Face;
+use std::ops::RangeInclusive;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Move {
+    // The face the move turns towards, clockwise as seen from this face
+    pub face: Face,
+    // Counted from `face`, starting at 1 for the outer layer
+    pub layers: RangeInclusive<usize>,
+    pub quarter_turns: u8,
+}
+
+impl Move {
+    /*
+    Outer (R), wide (Rw, r, 3Rw), inner slice (2R, M, E, S) and rotation (x, y, z) moves, with ', 2 or 2' after them
+    */
+    pub fn parse(token: &str, size: usize) -> Option<Move> {
+        let (body, quarter_turns) = if let Some(body) = token
+            .strip_suffix("2'")
+            .or_else(|| token.strip_suffix("'2"))
+        {
+            (body, 2)
+        } else if let Some(body) = token.strip_suffix('2') {
+            (body, 2)
+        } else if let Some(body) = token.strip_suffix('\'') {
+            (body, 3)
+        } else {
+            (token, 1)
+        };
+
+        let (face, layers) = match body {
+            "x" => (Face::R, 1..=size),
+            "y" => (Face::U, 1..=size),
+            "z" => (Face::F, 1..=size),
+            "M" => (Face::L, 2..=size - 1),
+            "E" => (Face::D, 2..=size - 1),
+            "S" => (Face::F, 2..=size - 1),
+            _ => parse_turn(body)?,
+        };
+        if layers.is_empty() || *layers.end() > size {
+            return None;
+        }
+        Some(Move {
+            face,
+            layers,
+            quarter_turns,
+        })
+    }
+}
+
+fn parse_turn(body: &str) -> Option<(Face, RangeInclusive<usize>)> {
+    let digits = body.chars().take_while(char::is_ascii_digit).count();
+    let depth: Option<usize> = match digits {
+        0 => None,
+        // Layers are counted from 1
+        _ => Some(body[..digits].parse().ok().filter(|&depth| depth > 0)?),
+    };
+    let mut letters = body[digits..].chars();
+    let letter = letters.next()?;
+    let rest = letters.as_str();
+
+    if letter.is_ascii_lowercase() && depth.is_none() && rest.is_empty() {
+        // A lowercase letter turns the outer two layers
+        let face = Face::from_letter(letter.to_ascii_uppercase())?;
+        return Some((face, 1..=2));
+    }
+    let face = Face::from_letter(letter)?;
+    match (depth, rest) {
+        (None, "") => Some((face, 1..=1)),
+        (Some(depth), "") => Some((face, depth..=depth)),
+        (depth, "w") => Some((face, 1..=depth.unwrap_or(2))),
+        _ => None,
+    }
+}
+
+pub fn parse_algorithm(algorithm: &str, size: usize) -> Result<Vec<Move>, String> {
+    algorithm
+        .split_whitespace()
+        .map(|token| Move::parse(token, size).ok_or(format!("Invalid move: {}", token)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(token: &str, size: usize) -> Option<(Face, RangeInclusive<usize>, u8)> {
+        Move::parse(token, size).map(|m| (m.face, m.layers, m.quarter_turns))
+    }
+
+    #[test]
+    fn parses_every_kind_of_move() {
+        assert_eq!(parse("R", 3), Some((Face::R, 1..=1, 1)));
+        assert_eq!(parse("U'", 3), Some((Face::U, 1..=1, 3)));
+        assert_eq!(parse("F2", 3), Some((Face::F, 1..=1, 2)));
+        assert_eq!(parse("D2'", 3), Some((Face::D, 1..=1, 2)));
+        assert_eq!(parse("Rw", 4), Some((Face::R, 1..=2, 1)));
+        assert_eq!(parse("r'", 3), Some((Face::R, 1..=2, 3)));
+        assert_eq!(parse("3Lw2", 6), Some((Face::L, 1..=3, 2)));
+        assert_eq!(parse("2B", 4), Some((Face::B, 2..=2, 1)));
+        assert_eq!(parse("M", 5), Some((Face::L, 2..=4, 1)));
+        assert_eq!(parse("E'", 3), Some((Face::D, 2..=2, 3)));
+        assert_eq!(parse("S2", 3), Some((Face::F, 2..=2, 2)));
+        assert_eq!(parse("x", 3), Some((Face::R, 1..=3, 1)));
+        assert_eq!(parse("y'", 4), Some((Face::U, 1..=4, 3)));
+        assert_eq!(parse("z2", 2), Some((Face::F, 1..=2, 2)));
+        assert_eq!(parse("0R", 3), None);
+        assert_eq!(parse("0Rw", 3), None);
+    }
+
+    #[test]
+    fn rejects_invalid_tokens() {
+        for token in ["", "Q", "R3", "R''", "Rx", "rw", "2r", "X", "Rw'w", "u2w"] {
+            assert_eq!(parse(token, 3), None, "{token}");
+        }
+        // Layers the cube does not have
+        assert_eq!(parse("4Rw", 3), None);
+        assert_eq!(parse("3R", 2), None);
+        assert_eq!(parse("M", 2), None);
+        assert_eq!(parse("E", 2), None);
+    }
+
+    #[test]
+    fn parse_algorithm_names_the_bad_move() {
+        assert_eq!(
+            parse_algorithm("  R  U'\tF2 ", 3).map(|moves| moves.len()),
+            Ok(3)
+        );
+        assert_eq!(
+            parse_algorithm("R U 5Rw", 4),
+            Err("Invalid move: 5Rw".to_string())
+        );
+    }
+}
@@ -0,0 +1,157 @@
+/*
+Showing times as text and reading them back, so a time looks the same everywhere it is shown
+*/
+
+use crate::{solve::Penalty, stats::Average};
+use std::time::Duration;
+
+const PLUS_TWO: Duration = Duration::from_secs(2);
+
+/*
+Times are shown as m:ss with as many decimal places as the precision, cut off rather than rounded like a stackmat
+*/
+#[derive(Clone, Copy)]
+pub struct TimeFormat {
+    // Decimal places, from 0 to 3
+    pub precision: u32,
+}
+
+impl Default for TimeFormat {
+    fn default() -> TimeFormat {
+        TimeFormat { precision: 2 }
+    }
+}
+
+impl TimeFormat {
+    pub fn time(&self, time: Duration) -> String {
+        self.milliseconds(time.as_millis() as i64)
+    }
+
+    /*
+    Negative times are shown with a minus sign in front
+    */
+    pub fn milliseconds(&self, milliseconds: i64) -> String {
+        let sign = if milliseconds < 0 { "-" } else { "" };
+        let milliseconds = milliseconds.unsigned_abs();
+        let minutes = milliseconds / 60_000;
+        let seconds = milliseconds / 1000 % 60;
+        let fraction = match self.precision.min(3) {
+            0 => String::new(),
+            precision => {
+                let fraction = milliseconds % 1000 / 10u64.pow(3 - precision);
+                format!(".{:0width$}", fraction, width = precision as usize)
+            }
+        };
+        if minutes > 0 {
+            format!("{sign}{minutes}:{seconds:02}{fraction}")
+        } else {
+            format!("{sign}{seconds}{fraction}")
+        }
+    }
+
+    /*
+    A +2 is shown as the time with the penalty and a + after it, a DNF as the time it would have been in brackets
+    */
+    pub fn solve(&self, time: Duration, penalty: Penalty) -> String {
+        match penalty {
+            Penalty::None => self.time(time),
+            Penalty::PlusTwo => format!("{}+", self.time(time + PLUS_TWO)),
+            // Running out of inspection time leaves no time to show
+            Penalty::Dnf if time.is_zero() => "DNF".to_owned(),
+            Penalty::Dnf => format!("DNF({})", self.time(time)),
+        }
+    }
+
+    pub fn average(&self, average: Option<Average>) -> String {
+        match average {
+            Some(Average::Time(time)) => self.time(time),
+            Some(Average::Dnf) => "DNF".to_owned(),
+            None => "-".to_owned(),
+        }
+    }
+}
+
+/*
+A Unix timestamp in milliseconds as the date and time in UTC
+*/
+pub fn timestamp(milliseconds: u64) -> String {
+    let seconds = milliseconds / 1000;
+    let (days, time) = (seconds / 86_400, seconds % 86_400);
+    // Howard Hinnant's days to civil date, with years starting in March so leap days come last
+    let shifted = days as i64 + 719_468;
+    let era = shifted.div_euclid(146_097);
+    let day_of_era = shifted.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = era * 400 + year_of_era + i64::from(month <= 2);
+    format!(
+        "{year}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+/*
+The reverse of `TimeFormat::solve`, also taking a DNF on its own, seconds past 60 and hours before the minutes
+*/
+pub fn parse_solve(text: &str) -> Option<(Duration, Penalty)> {
+    let text = text.trim();
+    if text.eq_ignore_ascii_case("DNF") {
+        return Some((Duration::ZERO, Penalty::Dnf));
+    }
+    if let Some(time) = text
+        .strip_prefix("DNF(")
+        .or_else(|| text.strip_prefix("dnf("))
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        return Some((parse_time(time)?, Penalty::Dnf));
+    }
+    if let Some(time) = text.strip_suffix('+') {
+        // The time shown already includes the penalty
+        let time = parse_time(time)?.checked_sub(PLUS_TWO)?;
+        return Some((time, Penalty::PlusTwo));
+    }
+    Some((parse_time(text)?, Penalty::None))
+}
+
+/*
+[[h:]m:]s[.fraction]
+*/
+fn parse_time(text: &str) -> Option<Duration> {
+    let mut parts = text.trim().rsplit(':');
+    let seconds = parts.next()?;
+    let (whole, fraction) = seconds.split_once('.').unwrap_or((seconds, ""));
+    if whole.is_empty() || fraction.len() > 3 || !is_digits(whole) || !is_digits(fraction) {
+        return None;
+    }
+    let mut milliseconds: u64 = whole.parse::<u64>().ok()? * 1000;
+    if !fraction.is_empty() {
+        milliseconds += fraction.parse::<u64>().ok()? * 10u64.pow(3 - fraction.len() as u32);
+    }
+    for unit in [60_000, 3_600_000] {
+        let Some(part) = parts.next() else {
+            break;
+        };
+        if part.is_empty() || !is_digits(part) {
+            return None;
+        }
+        milliseconds += part.parse::<u64>().ok()? * unit;
+    }
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(Duration::from_millis(milliseconds))
+}
+
+fn is_digits(text: &str) -> bool {
+    text.chars().all(|c| c.is_ascii_digit())
+}
@@ -0,0 +1,259 @@
+/*
+csTimer's JSON backups, as made by its export to file
+
+Each session is a `sessionN` array of solves, every solve being `[[penalty, time ms], scramble, comment, unix seconds]`. The penalty is 0, 2000 for a +2 or -1 for a DNF, and the time does not include it. Multi-phase solves have when each phase but the last ended after the time, the latest first. Session names and scramble types are in `properties.sessionData`, itself a JSON string.
+*/
+
+use super::invalid_data;
+use crate::{
+    puzzle::Puzzle,
+    session::Session,
+    solve::{Penalty, Solve},
+    storage,
+};
+use serde_json::{json, Map, Value};
+use std::{fs, io, path::Path, time::Duration};
+
+pub fn import(path: &Path) -> io::Result<Vec<Session>> {
+    let backup: Value = serde_json::from_slice(&fs::read(path)?)
+        .map_err(|err| invalid_data(format!("{}: {}", path.display(), err)))?;
+    let backup = backup
+        .as_object()
+        .ok_or_else(|| invalid_data("Not a csTimer backup"))?;
+    let session_data: Value = match backup
+        .get("properties")
+        .and_then(|properties| properties.get("sessionData"))
+        .and_then(Value::as_str)
+    {
+        Some(session_data) => serde_json::from_str(session_data)
+            .map_err(|err| invalid_data(format!("Invalid session data: {err}")))?,
+        None => Value::Null,
+    };
+
+    let mut sessions = Vec::new();
+    for (key, solves) in backup {
+        let Some(number) = key
+            .strip_prefix("session")
+            .and_then(|number| number.parse::<u64>().ok())
+        else {
+            continue;
+        };
+        let data = &session_data[number.to_string()];
+        let name = match &data["name"] {
+            Value::String(name) => name.clone(),
+            // csTimer names new sessions with their number
+            Value::Number(name) => name.to_string(),
+            _ => number.to_string(),
+        };
+        let puzzle = puzzle_from_scramble_type(data["opt"]["scrType"].as_str().unwrap_or("333"));
+        let solves = solves
+            .as_array()
+            .ok_or_else(|| invalid_data(format!("{key} is not a list of solves")))?
+            .iter()
+            .map(|solve| {
+                parse_solve(solve, puzzle)
+                    .ok_or_else(|| invalid_data(format!("Invalid solve in {key}: {solve}")))
+            })
+            .collect::<io::Result<Vec<Solve>>>()?;
+        let rank = data["rank"].as_u64().unwrap_or(number);
+        sessions.push((
+            rank,
+            Session {
+                name,
+                puzzle,
+                solves,
+            },
+        ));
+    }
+    sessions.sort_by_key(|&(rank, _)| rank);
+    Ok(sessions.into_iter().map(|(_, session)| session).collect())
+}
+
+pub fn export(path: &Path, sessions: &[Session]) -> io::Result<()> {
+    let mut backup = Map::new();
+    let mut session_data = Map::new();
+    for (index, session) in sessions.iter().enumerate() {
+        let number = index + 1;
+        let solves: Vec<Value> = session.solves.iter().map(solve_to_json).collect();
+        backup.insert(format!("session{number}"), Value::Array(solves));
+        session_data.insert(
+            number.to_string(),
+            json!({
+                "name": session.name,
+                "opt": { "scrType": scramble_type(session.puzzle) },
+                "rank": number,
+            }),
+        );
+    }
+    backup.insert(
+        "properties".to_string(),
+        json!({
+            "sessionData": Value::Object(session_data).to_string(),
+            "sessionN": sessions.len(),
+        }),
+    );
+    let contents = serde_json::to_vec(&Value::Object(backup))?;
+    storage::write_atomic(path, &contents)
+}
+
+fn parse_solve(solve: &Value, puzzle: Puzzle) -> Option<Solve> {
+    let result = solve.get(0)?.as_array()?;
+    let penalty = match result.first()?.as_i64()? {
+        -1 => Penalty::Dnf,
+        0 => Penalty::None,
+        _ => Penalty::PlusTwo,
+    };
+    let time = Duration::from_millis(result.get(1)?.as_u64()?);
+    let text = |index: usize| solve.get(index).and_then(Value::as_str).unwrap_or("");
+    let mut imported = Solve::new(time, penalty, text(1).to_string(), puzzle);
+    imported.comment = text(2).to_string();
+    imported.splits = result[2..]
+        .iter()
+        .rev()
+        .map(|split| split.as_u64().map(Duration::from_millis))
+        .collect::<Option<Vec<Duration>>>()?;
+    if let Some(timestamp) = solve.get(3).and_then(Value::as_u64) {
+        imported.timestamp = timestamp * 1000;
+    }
+    Some(imported)
+}
+
+fn solve_to_json(solve: &Solve) -> Value {
+    let penalty = match solve.penalty {
+        Penalty::None => 0,
+        Penalty::PlusTwo => 2000,
+        Penalty::Dnf => -1,
+    };
+    let mut result = vec![penalty, solve.time.as_millis() as i64];
+    result.extend(
+        solve
+            .splits
+            .iter()
+            .rev()
+            .map(|split| split.as_millis() as i64),
+    );
+    json!([
+        result,
+        solve.scramble,
+        solve.comment,
+        solve.timestamp / 1000,
+    ])
+}
+
+/*
+csTimer has many scramble types for each puzzle, the WCA ones are used when exporting
+*/
+fn puzzle_from_scramble_type(scramble_type: &str) -> Puzzle {
+    let prefixes = [
+        ("222", Puzzle::TwoByTwo),
+        ("444", Puzzle::FourByFour),
+        ("555", Puzzle::FiveByFive),
+        ("666", Puzzle::SixBySix),
+        ("777", Puzzle::SevenBySeven),
+        ("pyr", Puzzle::Pyraminx),
+        ("skb", Puzzle::Skewb),
+        ("mgm", Puzzle::Megaminx),
+        ("sq1", Puzzle::Square1),
+        ("sqrs", Puzzle::Square1),
+        ("clk", Puzzle::Clock),
+    ];
+    prefixes
+        .iter()
+        .find(|(prefix, _)| scramble_type.starts_with(prefix))
+        .map_or(Puzzle::ThreeByThree, |&(_, puzzle)| puzzle)
+}
+
+fn scramble_type(puzzle: Puzzle) -> &'static str {
+    match puzzle {
+        Puzzle::TwoByTwo => "222so",
+        Puzzle::ThreeByThree => "333",
+        Puzzle::FourByFour => "444wca",
+        Puzzle::FiveByFive => "555wca",
+        Puzzle::SixBySix => "666wca",
+        Puzzle::SevenBySeven => "777wca",
+        Puzzle::Pyraminx => "pyrso",
+        Puzzle::Skewb => "skbso",
+        Puzzle::Megaminx => "mgmp",
+        Puzzle::Square1 => "sqrs",
+        Puzzle::Clock => "clkwca",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn sample() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/cstimer.txt")
+    }
+
+    #[test]
+    fn imports_the_sample_backup() {
+        let sessions = import(&sample()).unwrap();
+        // In the order of their rank rather than their number
+        let names: Vec<&str> = sessions
+            .iter()
+            .map(|session| session.name.as_str())
+            .collect();
+        assert_eq!(names, ["1", "Square-1", "2x2 practice"]);
+        assert!(sessions[0].puzzle == Puzzle::ThreeByThree);
+        assert!(sessions[1].puzzle == Puzzle::Square1);
+        assert!(sessions[2].puzzle == Puzzle::TwoByTwo);
+
+        let solves = &sessions[0].solves;
+        assert_eq!(solves.len(), 4);
+        let penalties = [Penalty::None, Penalty::PlusTwo, Penalty::Dnf, Penalty::None];
+        assert!(solves.iter().map(|solve| solve.penalty).eq(penalties));
+        // Times do not include the +2
+        assert_eq!(solves[1].time, Duration::from_millis(12907));
+        assert_eq!(solves[3].time, Duration::from_millis(10544));
+        assert_eq!(solves[0].timestamp, 1_704_103_200_000);
+        assert_eq!(solves[2].comment, "pop on PLL");
+        assert_eq!(
+            solves[0].scramble,
+            "D2 B2 U' R2 U F2 U' L2 D' B2 R' U2 B' L U' F2 R' F' U2 R"
+        );
+        assert!(solves
+            .iter()
+            .all(|solve| solve.puzzle == Puzzle::ThreeByThree));
+        assert_eq!(sessions[2].solves[1].comment, "good one");
+        assert_eq!(
+            solves[3].splits,
+            [Duration::from_millis(3911), Duration::from_millis(7302)]
+        );
+        assert!(solves[..3].iter().all(|solve| solve.splits.is_empty()));
+    }
+
+    #[test]
+    fn exported_backups_import_the_same() {
+        let sessions = import(&sample()).unwrap();
+        let path = std::env::temp_dir().join(format!("cstimer_export_{}.txt", std::process::id()));
+        export(&path, &sessions).unwrap();
+        let exported = import(&path);
+        fs::remove_file(&path).unwrap();
+        let exported = exported.unwrap();
+
+        assert_eq!(exported.len(), sessions.len());
+        for (exported, session) in exported.iter().zip(&sessions) {
+            assert_eq!(exported.name, session.name);
+            assert!(exported.puzzle == session.puzzle);
+            assert_eq!(exported.solves.len(), session.solves.len());
+            for (exported, solve) in exported.solves.iter().zip(&session.solves) {
+                assert_eq!(exported.time, solve.time);
+                assert!(exported.penalty == solve.penalty);
+                assert_eq!(exported.scramble, solve.scramble);
+                assert_eq!(exported.comment, solve.comment);
+                assert_eq!(exported.timestamp, solve.timestamp);
+                assert_eq!(exported.splits, solve.splits);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_backups() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+        let result = import(&path);
+        assert!(result.is_err_and(|err| err.kind() == io::ErrorKind::InvalidData));
+    }
+}
@@ -0,0 +1,105 @@
+/*
+CSV files with CubeDesk's solve fields as columns
+
+Columns are found by their header so their order does not matter. Times are in seconds, `time` including a +2 and `raw_time` not, penalties are the `dnf` and `plus_two` booleans, cube types are WCA event IDs and `started_at` and `ended_at` are milliseconds since the Unix epoch, one of which every solve needs.
+*/
+
+use super::invalid_data;
+use crate::{
+    puzzle::Puzzle,
+    session::Session,
+    solve::{Penalty, Solve},
+    storage,
+};
+use csv::{ReaderBuilder, StringRecord, Writer};
+use std::{io, path::Path, time::Duration};
+
+const HEADER: [&str; 9] = [
+    "time",
+    "raw_time",
+    "cube_type",
+    "scramble",
+    "dnf",
+    "plus_two",
+    "started_at",
+    "ended_at",
+    "notes",
+];
+
+/*
+The solves of the puzzle in the file
+*/
+pub fn import(path: &Path, puzzle: Puzzle) -> io::Result<Vec<Solve>> {
+    let mut reader = ReaderBuilder::new().from_path(path)?;
+    let header = reader.headers()?.clone();
+    let column = |record: &StringRecord, name: &str| -> Option<String> {
+        let index = header.iter().position(|column| column == name)?;
+        record.get(index).map(str::to_string)
+    };
+
+    let mut solves = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let invalid = || invalid_data(format!("Invalid solve: {}", record.as_slice()));
+        let cube_type = column(&record, "cube_type").unwrap_or_default();
+        if Puzzle::from_id(&cube_type) != Some(puzzle) {
+            continue;
+        }
+        let flag =
+            |name: &str| column(&record, name).is_some_and(|value| value == "true" || value == "1");
+        let penalty = if flag("dnf") {
+            Penalty::Dnf
+        } else if flag("plus_two") {
+            Penalty::PlusTwo
+        } else {
+            Penalty::None
+        };
+        let seconds = match column(&record, "raw_time") {
+            Some(raw_time) if !raw_time.is_empty() => raw_time,
+            _ => column(&record, "time").ok_or_else(invalid)?,
+        };
+        let seconds: f64 = seconds.parse().map_err(|_| invalid())?;
+        let time = Duration::from_millis((seconds * 1000.0).round() as u64);
+        let scramble = column(&record, "scramble").unwrap_or_default();
+        let mut solve = Solve::new(time, penalty, scramble, puzzle);
+        let timestamp = |name: &str| {
+            column(&record, name)
+                .filter(|value| !value.is_empty())
+                .map(|value| value.parse::<u64>().map_err(|_| invalid()))
+        };
+        solve.timestamp = match (timestamp("ended_at"), timestamp("started_at")) {
+            (Some(ended_at), _) => ended_at?,
+            (None, Some(started_at)) => started_at? + time.as_millis() as u64,
+            // Solves are told apart by when they ended, so without it the same solve would be added again on every import
+            (None, None) => return Err(invalid()),
+        };
+        solve.comment = column(&record, "notes").unwrap_or_default();
+        solves.push(solve);
+    }
+    Ok(solves)
+}
+
+pub fn export(path: &Path, session: &Session) -> io::Result<()> {
+    let mut writer = Writer::from_writer(Vec::new());
+    writer.write_record(HEADER)?;
+    for solve in &session.solves {
+        let seconds = |time: Duration| format!("{:.3}", time.as_secs_f64());
+        let time = solve.result().unwrap_or(solve.time);
+        let started_at = solve
+            .timestamp
+            .saturating_sub(solve.time.as_millis() as u64);
+        writer.write_record([
+            &seconds(time),
+            &seconds(solve.time),
+            session.puzzle.id(),
+            &solve.scramble,
+            &(solve.penalty == Penalty::Dnf).to_string(),
+            &(solve.penalty == Penalty::PlusTwo).to_string(),
+            &started_at.to_string(),
+            &solve.timestamp.to_string(),
+            &solve.comment,
+        ])?;
+    }
+    let contents = writer.into_inner().map_err(|err| err.into_error())?;
+    storage::write_atomic(path, &contents)
+}
@@ -0,0 +1,51 @@
+/*
+Reading and writing the files of other timers, so solves can move between them and this one
+*/
+
+pub mod cstimer;
+pub mod cubedesk;
+pub mod twisty_timer;
+
+use std::{io, path::PathBuf};
+
+#[derive(Clone, Copy)]
+pub enum Format {
+    // Backups of every session
+    CsTimer,
+    // CSV files of a single session's solves
+    TwistyTimer,
+    CubeDesk,
+}
+
+impl Format {
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::CsTimer => "csTimer",
+            Format::TwistyTimer => "Twisty Timer",
+            Format::CubeDesk => "CubeDesk",
+        }
+    }
+
+    pub fn from_key(key: char) -> Option<Format> {
+        match key {
+            'c' => Some(Format::CsTimer),
+            't' => Some(Format::TwistyTimer),
+            'd' => Some(Format::CubeDesk),
+            _ => None,
+        }
+    }
+}
+
+/*
+A path typed by the user, with ~ standing for the home directory like in a shell
+*/
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
@@ -0,0 +1,163 @@
+/*
+Twisty Timer's CSV backups
+
+Every solve is a row of `Puzzle;Category;Time(millis);Date(millis);Scramble;Penalty;Comment` with every field quoted. The penalty is 0, 1 for a +2 or 2 for a DNF, and the time already includes a +2.
+*/
+
+use super::invalid_data;
+use crate::{
+    puzzle::Puzzle,
+    session::Session,
+    solve::{Penalty, Solve},
+    storage,
+};
+use csv::{QuoteStyle, ReaderBuilder, WriterBuilder};
+use std::{io, path::Path, time::Duration};
+
+const HEADER: [&str; 7] = [
+    "Puzzle",
+    "Category",
+    "Time(millis)",
+    "Date(millis)",
+    "Scramble",
+    "Penalty",
+    "Comment",
+];
+const PLUS_TWO: Duration = Duration::from_secs(2);
+
+/*
+The solves of the puzzle in the file
+*/
+pub fn import(path: &Path, puzzle: Puzzle) -> io::Result<Vec<Solve>> {
+    let mut reader = ReaderBuilder::new().delimiter(b';').from_path(path)?;
+    let mut solves = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let invalid = || invalid_data(format!("Invalid solve: {}", record.as_slice()));
+        // Rows can be shorter than expected when every row of the file is
+        let field = |index: usize| record.get(index).ok_or_else(invalid);
+        if puzzle_from_name(field(0)?) != Some(puzzle) {
+            continue;
+        }
+        let milliseconds = |index: usize| field(index)?.parse::<u64>().map_err(|_| invalid());
+        let mut time = Duration::from_millis(milliseconds(2)?);
+        let penalty = match field(5)? {
+            "0" => Penalty::None,
+            "1" => Penalty::PlusTwo,
+            "2" => Penalty::Dnf,
+            _ => return Err(invalid()),
+        };
+        if penalty == Penalty::PlusTwo {
+            time = time.saturating_sub(PLUS_TWO);
+        }
+        let mut solve = Solve::new(time, penalty, field(4)?.to_string(), puzzle);
+        solve.timestamp = milliseconds(3)?;
+        solve.comment = field(6)?.to_string();
+        solves.push(solve);
+    }
+    Ok(solves)
+}
+
+pub fn export(path: &Path, session: &Session) -> io::Result<()> {
+    let mut writer = WriterBuilder::new()
+        .delimiter(b';')
+        .quote_style(QuoteStyle::Always)
+        .from_writer(Vec::new());
+    writer.write_record(HEADER)?;
+    for solve in &session.solves {
+        let (time, penalty) = match solve.penalty {
+            Penalty::None => (solve.time, "0"),
+            Penalty::PlusTwo => (solve.time + PLUS_TWO, "1"),
+            Penalty::Dnf => (solve.time, "2"),
+        };
+        writer.write_record([
+            puzzle_name(session.puzzle),
+            "Normal",
+            &time.as_millis().to_string(),
+            &solve.timestamp.to_string(),
+            &solve.scramble,
+            penalty,
+            &solve.comment,
+        ])?;
+    }
+    let contents = writer.into_inner().map_err(|err| err.into_error())?;
+    storage::write_atomic(path, &contents)
+}
+
+const PUZZLE_NAMES: [(Puzzle, &str); 11] = [
+    (Puzzle::TwoByTwo, "222"),
+    (Puzzle::ThreeByThree, "333"),
+    (Puzzle::FourByFour, "444"),
+    (Puzzle::FiveByFive, "555"),
+    (Puzzle::SixBySix, "666"),
+    (Puzzle::SevenBySeven, "777"),
+    (Puzzle::Pyraminx, "pyra"),
+    (Puzzle::Skewb, "skewb"),
+    (Puzzle::Megaminx, "mega"),
+    (Puzzle::Square1, "sq1"),
+    (Puzzle::Clock, "clock"),
+];
+
+fn puzzle_name(puzzle: Puzzle) -> &'static str {
+    PUZZLE_NAMES
+        .iter()
+        .find(|&&(candidate, _)| candidate == puzzle)
+        .map(|&(_, name)| name)
+        .unwrap()
+}
+
+fn puzzle_from_name(name: &str) -> Option<Puzzle> {
+    PUZZLE_NAMES
+        .iter()
+        .find(|&&(_, candidate)| candidate == name)
+        .map(|&(puzzle, _)| puzzle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn import_text(name: &str, contents: &str) -> io::Result<Vec<Solve>> {
+        let path = std::env::temp_dir().join(format!("{name}_{}.csv", std::process::id()));
+        fs::write(&path, contents)?;
+        let solves = import(&path, Puzzle::ThreeByThree);
+        fs::remove_file(&path)?;
+        solves
+    }
+
+    #[test]
+    fn imports_every_field() {
+        let solves = import_text(
+            "twisty_timer_import",
+            "\"Puzzle\";\"Category\";\"Time(millis)\";\"Date(millis)\";\"Scramble\";\"Penalty\";\"Comment\"\n\
+             \"333\";\"Normal\";\"12345\";\"1704103200000\";\"R U R' U'\";\"1\";\"nice\"\n\
+             \"222\";\"Normal\";\"3000\";\"1704103300000\";\"R U\";\"0\";\"\"\n",
+        )
+        .unwrap();
+        assert_eq!(solves.len(), 1);
+        // The +2 is taken off the time
+        assert_eq!(solves[0].time, Duration::from_millis(10345));
+        assert!(solves[0].penalty == Penalty::PlusTwo);
+        assert_eq!(solves[0].timestamp, 1_704_103_200_000);
+        assert_eq!(solves[0].scramble, "R U R' U'");
+        assert_eq!(solves[0].comment, "nice");
+    }
+
+    #[test]
+    fn rejects_truncated_rows() {
+        // Every row as short as the header
+        let result = import_text(
+            "twisty_timer_short",
+            "\"333\";\"Normal\";\"1000\"\n\"333\";\"Normal\";\"1000\"\n",
+        );
+        assert!(result.is_err_and(|err| err.kind() == io::ErrorKind::InvalidData));
+        // One row shorter than the rest
+        let result = import_text(
+            "twisty_timer_truncated",
+            "\"Puzzle\";\"Category\";\"Time(millis)\";\"Date(millis)\";\"Scramble\";\"Penalty\";\"Comment\"\n\
+             \"333\";\"Normal\";\"12345\"\n",
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,270 @@
+/*
+Undo and redo for changes to the sessions, saved with them so they survive a restart
+
+Solves are found by their timestamp, time and scramble rather than their place in the list, as importing can move them. Sessions are found by their place in the list, so every change that removes one is in the history.
+*/
+
+use crate::{
+    session::{self, Session, Sessions},
+    solve::Solve,
+};
+use serde::{Deserialize, Serialize};
+
+// Older changes are forgotten past this many
+const LIMIT: usize = 100;
+
+#[derive(Serialize, Deserialize)]
+pub enum Edit {
+    AddSolve {
+        session: usize,
+        solve: Solve,
+    },
+    DeleteSolve {
+        session: usize,
+        solve: Solve,
+    },
+    // A new penalty or comment
+    ChangeSolve {
+        session: usize,
+        before: Solve,
+        after: Solve,
+    },
+    // The indexes are from before the merge, only the solves that were not already in `into` were added
+    MergeSessions {
+        merged: usize,
+        into: usize,
+        session: Session,
+        added: Vec<Solve>,
+    },
+    DeleteSession {
+        index: usize,
+        session: Session,
+    },
+    // Only the solves that were not already in each session were added, the new sessions went on the end starting at `first_created`
+    Import {
+        added: Vec<(usize, Vec<Solve>)>,
+        first_created: usize,
+        created: Vec<Session>,
+    },
+    RenameSession {
+        index: usize,
+        before: String,
+        after: String,
+    },
+}
+
+impl Edit {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Edit::AddSolve { .. } => "new solve",
+            Edit::DeleteSolve { .. } => "deleted solve",
+            Edit::ChangeSolve { .. } => "solve change",
+            Edit::MergeSessions { .. } => "merge",
+            Edit::DeleteSession { .. } => "deleted session",
+            Edit::Import { .. } => "import",
+            Edit::RenameSession { .. } => "rename",
+        }
+    }
+
+    /*
+    Makes the change to the sessions, None if they no longer have what it changes
+    */
+    fn apply(&self, sessions: &mut Sessions) -> Option<()> {
+        match self {
+            Edit::AddSolve { session, solve } => insert(sessions, *session, solve),
+            Edit::DeleteSolve { session, solve } => remove(sessions, *session, solve),
+            Edit::ChangeSolve {
+                session,
+                before,
+                after,
+            } => replace(sessions, *session, before, after),
+            Edit::MergeSessions {
+                merged,
+                into,
+                added,
+                ..
+            } => {
+                if *merged >= sessions.sessions.len() || merged == into {
+                    return None;
+                }
+                let solves = &mut sessions.sessions.get_mut(*into)?.solves;
+                session::add_solves(solves, added.clone());
+                sessions.remove(*merged);
+                Some(())
+            }
+            Edit::DeleteSession { index, .. } => {
+                if *index >= sessions.sessions.len() || sessions.sessions.len() == 1 {
+                    return None;
+                }
+                sessions.remove(*index);
+                Some(())
+            }
+            Edit::Import {
+                added,
+                first_created,
+                created,
+            } => {
+                let count = sessions.sessions.len();
+                if *first_created != count || added.iter().any(|&(index, _)| index >= count) {
+                    return None;
+                }
+                for (index, solves) in added {
+                    session::add_solves(&mut sessions.sessions[*index].solves, solves.clone());
+                }
+                sessions.sessions.extend(created.iter().cloned());
+                Some(())
+            }
+            Edit::RenameSession {
+                index,
+                before,
+                after,
+            } => rename(sessions, *index, before, after),
+        }
+    }
+
+    fn reverse(&self, sessions: &mut Sessions) -> Option<()> {
+        match self {
+            Edit::AddSolve { session, solve } => remove(sessions, *session, solve),
+            Edit::DeleteSolve { session, solve } => insert(sessions, *session, solve),
+            Edit::ChangeSolve {
+                session,
+                before,
+                after,
+            } => replace(sessions, *session, after, before),
+            Edit::MergeSessions {
+                merged,
+                into,
+                session,
+                added,
+            } => {
+                if *merged > sessions.sessions.len() || *into > sessions.sessions.len() {
+                    return None;
+                }
+                sessions.insert(*merged, session.clone());
+                let solves = &mut sessions.sessions.get_mut(*into)?.solves;
+                solves.retain(|solve| !added.iter().any(|added| session::is_same(solve, added)));
+                Some(())
+            }
+            Edit::DeleteSession { index, session } => {
+                if *index > sessions.sessions.len() {
+                    return None;
+                }
+                sessions.insert(*index, session.clone());
+                Some(())
+            }
+            Edit::Import {
+                added,
+                first_created,
+                created,
+            } => {
+                // Sessions added since would have gone after the imported ones
+                let end = first_created + created.len();
+                if end != sessions.sessions.len()
+                    || added.iter().any(|&(index, _)| index >= *first_created)
+                {
+                    return None;
+                }
+                for index in (*first_created..end).rev() {
+                    sessions.remove(index);
+                }
+                for (index, solves) in added {
+                    sessions.sessions[*index]
+                        .solves
+                        .retain(|solve| !solves.iter().any(|added| session::is_same(solve, added)));
+                }
+                Some(())
+            }
+            Edit::RenameSession {
+                index,
+                before,
+                after,
+            } => rename(sessions, *index, after, before),
+        }
+    }
+}
+
+fn insert(sessions: &mut Sessions, session: usize, solve: &Solve) -> Option<()> {
+    let solves = &mut sessions.sessions.get_mut(session)?.solves;
+    // Solves are kept in the order they were done
+    let index = solves.partition_point(|other| other.timestamp <= solve.timestamp);
+    solves.insert(index, solve.clone());
+    Some(())
+}
+
+fn remove(sessions: &mut Sessions, session: usize, solve: &Solve) -> Option<()> {
+    let solves = &mut sessions.sessions.get_mut(session)?.solves;
+    let index = solves
+        .iter()
+        .position(|other| session::is_same(other, solve))?;
+    solves.remove(index);
+    Some(())
+}
+
+fn rename(sessions: &mut Sessions, index: usize, old: &str, new: &str) -> Option<()> {
+    let session = sessions.sessions.get_mut(index)?;
+    if session.name != old {
+        return None;
+    }
+    session.name = new.to_string();
+    Some(())
+}
+
+fn replace(sessions: &mut Sessions, session: usize, old: &Solve, new: &Solve) -> Option<()> {
+    let solves = &mut sessions.sessions.get_mut(session)?.solves;
+    let solve = solves
+        .iter_mut()
+        .find(|other| session::is_same(other, old))?;
+    *solve = new.clone();
+    Some(())
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct History {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+}
+
+impl History {
+    /*
+    Remembers a change that has just been made, which can no longer be redone past
+    */
+    pub fn record(&mut self, edit: Edit) {
+        self.redo.clear();
+        self.undo.push(edit);
+        if self.undo.len() > LIMIT {
+            self.undo.remove(0);
+        }
+    }
+
+    /*
+    Reverses the last change and says what it was, or why nothing was undone
+    */
+    pub fn undo(&mut self, sessions: &mut Sessions) -> Result<String, String> {
+        let edit = self.undo.pop().ok_or("Nothing to undo")?;
+        let description = edit.description();
+        if edit.reverse(sessions).is_none() {
+            return Err(self.forget(description));
+        }
+        self.redo.push(edit);
+        Ok(format!("Undid the {description}"))
+    }
+
+    pub fn redo(&mut self, sessions: &mut Sessions) -> Result<String, String> {
+        let edit = self.redo.pop().ok_or("Nothing to redo")?;
+        let description = edit.description();
+        if edit.apply(sessions).is_none() {
+            return Err(self.forget(description));
+        }
+        self.undo.push(edit);
+        Ok(format!("Redid the {description}"))
+    }
+
+    /*
+    The sessions were changed some other way, so the rest of the history cannot be trusted either
+    */
+    fn forget(&mut self, description: &str) -> String {
+        self.undo.clear();
+        self.redo.clear();
+        format!("Could not find what the {description} changed, the history has been cleared")
+    }
+}
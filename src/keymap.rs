@@ -0,0 +1,298 @@
+/*
+Which keys do what, from the defaults and the user's `keymap.toml`
+
+Each line of the file binds an action to a key or a list of keys, replacing its default ones:
+
+    quit = "q"
+    previous_solve = ["up", "k"]
+
+Keys are single characters or names like space, enter, esc, tab, up and f1, with `ctrl+` in front for the control key.
+*/
+
+use crate::storage;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::{fs, io};
+
+const KEYMAP_FILE: &str = "keymap.toml";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    Timer,
+    Quit,
+    Sessions,
+    Settings,
+    NextSession,
+    PreviousSession,
+    EnterTime,
+    NextSolve,
+    PreviousSolve,
+    SolveDetails,
+    PlusTwo,
+    Dnf,
+    DeleteSolve,
+    Undo,
+    Redo,
+    Unselect,
+}
+
+impl Action {
+    pub const ALL: [Action; 16] = [
+        Action::Timer,
+        Action::Quit,
+        Action::Sessions,
+        Action::Settings,
+        Action::NextSession,
+        Action::PreviousSession,
+        Action::EnterTime,
+        Action::NextSolve,
+        Action::PreviousSolve,
+        Action::SolveDetails,
+        Action::PlusTwo,
+        Action::Dnf,
+        Action::DeleteSolve,
+        Action::Undo,
+        Action::Redo,
+        Action::Unselect,
+    ];
+
+    // The name in the keymap file
+    fn name(self) -> &'static str {
+        match self {
+            Action::Timer => "start_timer",
+            Action::Quit => "quit",
+            Action::Sessions => "sessions",
+            Action::Settings => "settings",
+            Action::NextSession => "next_session",
+            Action::PreviousSession => "previous_session",
+            Action::EnterTime => "enter_time",
+            Action::NextSolve => "next_solve",
+            Action::PreviousSolve => "previous_solve",
+            Action::SolveDetails => "solve_details",
+            Action::PlusTwo => "plus_two",
+            Action::Dnf => "dnf",
+            Action::DeleteSolve => "delete_solve",
+            Action::Undo => "undo",
+            Action::Redo => "redo",
+            Action::Unselect => "unselect_solve",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Timer => "Start/stop",
+            Action::Quit => "Quit",
+            Action::Sessions => "Sessions",
+            Action::Settings => "Settings",
+            Action::NextSession => "Next session",
+            Action::PreviousSession => "Previous session",
+            Action::EnterTime => "Enter a time",
+            Action::NextSolve => "Next solve",
+            Action::PreviousSolve => "Previous solve",
+            Action::SolveDetails => "Solve details",
+            Action::PlusTwo => "+2",
+            Action::Dnf => "DNF",
+            Action::DeleteSolve => "Delete solve",
+            Action::Undo => "Undo",
+            Action::Redo => "Redo",
+            Action::Unselect => "Unselect",
+        }
+    }
+
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Action::Timer => &["space"],
+            Action::Quit => &["q"],
+            Action::Sessions => &["s"],
+            Action::Settings => &["o"],
+            Action::NextSession => &["tab"],
+            Action::PreviousSession => &["backtab"],
+            Action::EnterTime => &["m"],
+            Action::NextSolve => &["down"],
+            Action::PreviousSolve => &["up"],
+            Action::SolveDetails => &["enter"],
+            Action::PlusTwo => &["2"],
+            Action::Dnf => &["d"],
+            Action::DeleteSolve => &["x", "delete"],
+            Action::Undo => &["u"],
+            Action::Redo => &["r"],
+            Action::Unselect => &["left"],
+        }
+    }
+}
+
+const KEY_NAMES: [(&str, KeyCode); 13] = [
+    ("space", KeyCode::Char(' ')),
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Key {
+    code: KeyCode,
+    ctrl: bool,
+}
+
+impl Key {
+    fn parse(name: &str) -> Option<Key> {
+        let (ctrl, name) = match name.strip_prefix("ctrl+") {
+            Some(rest) => (true, rest),
+            None => (false, name),
+        };
+        let mut chars = name.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => {
+                let lower = name.to_lowercase();
+                match KEY_NAMES.iter().find(|&&(key_name, _)| key_name == lower) {
+                    Some(&(_, code)) => code,
+                    None => match lower.strip_prefix('f').map(str::parse) {
+                        Some(Ok(number @ 1..=12)) => KeyCode::F(number),
+                        _ => return None,
+                    },
+                }
+            }
+        };
+        Some(Key { code, ctrl })
+    }
+
+    fn from_event(event: &KeyEvent) -> Key {
+        Key {
+            code: event.code,
+            ctrl: event.modifiers.contains(KeyModifiers::CONTROL),
+        }
+    }
+
+    fn name(&self) -> String {
+        let name = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::F(number) => format!("F{number}"),
+            KeyCode::BackTab => "Shift+Tab".to_string(),
+            code => {
+                let name = KEY_NAMES
+                    .iter()
+                    .find(|&&(_, key_code)| key_code == code)
+                    .map_or("?", |&(name, _)| name);
+                // Capitalised so they stand out from letter keys
+                let mut chars = name.chars();
+                chars.next().map_or_else(String::new, |first| {
+                    first.to_uppercase().chain(chars).collect()
+                })
+            }
+        };
+        if self.ctrl {
+            format!("Ctrl+{name}")
+        } else {
+            name
+        }
+    }
+}
+
+pub struct Keymap {
+    bindings: Vec<(Action, Vec<Key>)>,
+}
+
+impl Keymap {
+    /*
+    The defaults with the user's bindings on top, an error if the file is invalid or gives a key more than one action
+    */
+    pub fn load() -> io::Result<Keymap> {
+        let path = storage::config_dir()?.join(KEYMAP_FILE);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+        Keymap::parse(&contents).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), err),
+            )
+        })
+    }
+
+    fn parse(contents: &str) -> Result<Keymap, String> {
+        let table: toml::Table = contents.parse().map_err(|err| format!("{err}"))?;
+        let mut bindings = Vec::new();
+        for action in Action::ALL {
+            let names: Vec<&str> = match table.get(action.name()) {
+                None => action.default_keys().to_vec(),
+                Some(toml::Value::String(name)) => vec![name.as_str()],
+                Some(toml::Value::Array(names)) => names
+                    .iter()
+                    .map(|name| {
+                        name.as_str().ok_or_else(|| {
+                            format!("{} has a key that is not a string", action.name())
+                        })
+                    })
+                    .collect::<Result<_, _>>()?,
+                Some(_) => {
+                    return Err(format!(
+                        "{} should be a key or a list of keys",
+                        action.name()
+                    ))
+                }
+            };
+            let keys = names
+                .iter()
+                .map(|name| Key::parse(name).ok_or_else(|| format!("Unknown key \"{name}\"")))
+                .collect::<Result<Vec<Key>, String>>()?;
+            bindings.push((action, keys));
+        }
+        if let Some(name) = table
+            .keys()
+            .find(|name| !Action::ALL.iter().any(|action| action.name() == *name))
+        {
+            return Err(format!("Unknown action \"{name}\""));
+        }
+
+        for (index, (action, keys)) in bindings.iter().enumerate() {
+            for key in keys {
+                if let Some((other, _)) = bindings[index + 1..]
+                    .iter()
+                    .find(|(_, other_keys)| other_keys.contains(key))
+                {
+                    return Err(format!(
+                        "{} is bound to both {} and {}",
+                        key.name(),
+                        action.name(),
+                        other.name()
+                    ));
+                }
+            }
+        }
+        Ok(Keymap { bindings })
+    }
+
+    pub fn action(&self, event: &KeyEvent) -> Option<Action> {
+        let key = Key::from_event(event);
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.contains(&key))
+            .map(|&(action, _)| action)
+    }
+
+    /*
+    Every action with the names of its keys, for the help
+    */
+    pub fn help(&self) -> Vec<(&'static str, String)> {
+        self.bindings
+            .iter()
+            .filter(|(_, keys)| !keys.is_empty())
+            .map(|(action, keys)| {
+                let names: Vec<String> = keys.iter().map(Key::name).collect();
+                (action.description(), names.join("/"))
+            })
+            .collect()
+    }
+}
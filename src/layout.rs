@@ -0,0 +1,83 @@
+/*
+Where each part of the screen goes for the size of the terminal
+
+The timer, with the scramble, always gets space. The list of times and the scramble preview sit beside it on wide terminals, the list goes under it on tall narrow ones, and both are left out when there is no room for them.
+*/
+
+use tui::layout::Rect;
+
+// Below these the panels are hidden rather than squashed
+const SIDE_BY_SIDE_WIDTH: u16 = 80;
+const STACKED_HEIGHT: u16 = 30;
+const PREVIEW_HEIGHT: u16 = 24;
+const FOOTER_HEIGHT: u16 = 12;
+
+pub struct ScreenLayout {
+    pub timer: Rect,
+    pub times: Option<Rect>,
+    pub preview: Option<Rect>,
+    // One line of keybinds along the bottom
+    pub footer: Option<Rect>,
+}
+
+pub fn split(area: Rect) -> ScreenLayout {
+    let (body, footer) = if area.height >= FOOTER_HEIGHT {
+        let (body, footer) = split_rows(area, area.height - 1);
+        (body, Some(footer))
+    } else {
+        (area, None)
+    };
+
+    if body.width >= SIDE_BY_SIDE_WIDTH {
+        let side_width = (body.width * 3 / 10).clamp(34, 50);
+        let side = Rect {
+            width: side_width,
+            ..body
+        };
+        let timer = Rect {
+            x: body.x + side_width,
+            width: body.width - side_width,
+            ..body
+        };
+        let (preview, times) = if body.height >= PREVIEW_HEIGHT {
+            let (preview, times) = split_rows(side, side.height * 2 / 5);
+            (Some(preview), times)
+        } else {
+            (None, side)
+        };
+        ScreenLayout {
+            timer,
+            times: Some(times),
+            preview,
+            footer,
+        }
+    } else if body.height >= STACKED_HEIGHT {
+        let (timer, times) = split_rows(body, body.height * 3 / 5);
+        ScreenLayout {
+            timer,
+            times: Some(times),
+            preview: None,
+            footer,
+        }
+    } else {
+        ScreenLayout {
+            timer: body,
+            times: None,
+            preview: None,
+            footer,
+        }
+    }
+}
+
+/*
+The first `height` rows and the rest
+*/
+fn split_rows(area: Rect, height: u16) -> (Rect, Rect) {
+    let top = Rect { height, ..area };
+    let bottom = Rect {
+        y: area.y + height,
+        height: area.height - height,
+        ..area
+    };
+    (top, bottom)
+}
@@ -1,19 +1,58 @@
+use config::{Config, Setting};
 use crossterm::{
-    event::{self, DisableMouseCapture, Event, KeyCode},
+    event::{
+        self, DisableMouseCapture, Event, KeyCode, KeyEventKind, KeyboardEnhancementFlags,
+        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{
+        disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
 };
+use format::TimeFormat;
+use formats::Format;
+use history::{Edit, History};
+use keymap::{Action, Keymap};
+use puzzle::Puzzle;
+use scramble::ScrambleQueue;
+use session::{Session, Sessions};
+use solve::{Penalty, Solve};
+use stats::Statistics;
 use std::{
     error::Error,
-    io,
+    io, mem,
+    path::Path,
+    process,
     time::{Duration, Instant},
 };
+use theme::Theme;
+use timer::{Timer, TimerKey, TimerStatus};
 use tui::{
     backend::{Backend, CrosstermBackend},
     widgets::ListState,
     Terminal,
 };
 
+mod big_digits;
+mod clipboard;
+mod config;
+mod cube;
+mod format;
+mod formats;
+mod history;
+mod keymap;
+mod layout;
+mod method;
+mod puzzle;
+mod scramble;
+mod session;
+mod solve;
+mod stats;
+mod storage;
+mod theme;
+mod timer;
+mod two_phase;
 mod ui;
 
 struct StatefulList<T> {
@@ -21,14 +60,6 @@ struct StatefulList<T> {
     items: Vec<T>,
 }
 
-#[derive(PartialEq)]
-
-enum TimerStatus {
-    COUNTDOWN,
-    COUNTUP,
-    PAUSED,
-}
-
 impl<T> StatefulList<T> {
     fn with_items(items: Vec<T>) -> StatefulList<T> {
         StatefulList {
@@ -38,6 +69,11 @@ impl<T> StatefulList<T> {
     }
 
     fn next(&mut self) {
+        // Nothing to select, as loaded sessions can have no solves
+        if self.items.is_empty() {
+            self.state.select(None);
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i >= self.items.len() - 1 {
@@ -52,12 +88,17 @@ impl<T> StatefulList<T> {
     }
 
     fn previous(&mut self) {
+        if self.items.is_empty() {
+            self.state.select(None);
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i == 0 {
                     self.items.len() - 1
                 } else {
-                    i - 1
+                    // The list may have got shorter since it was selected
+                    (i - 1).min(self.items.len() - 1)
                 }
             }
             None => 0,
@@ -70,87 +111,731 @@ impl<T> StatefulList<T> {
     }
 }
 
+/*
+What the keys currently do
+*/
+enum Mode {
+    Timer,
+    // The list of sessions is open
+    Sessions,
+    // Typing an answer to a question about the selected session
+    Prompt { prompt: Prompt, text: String },
+    // Waiting for the key of the format to import or export
+    ChooseFormat { export: bool },
+    // Waiting for y to delete the selected session
+    ConfirmDelete,
+    // Typing the time of a solve done without the timer
+    ManualEntry { text: String, invalid: bool },
+    // The settings screen is open, with the new value while a number is being typed
+    Settings { editing: Option<String> },
+    // The details of a solve in the list of times are open
+    SolveDetails { index: usize, detail: Detail },
+}
+
+/*
+What is being done to the open solve
+*/
+enum Detail {
+    Viewing,
+    Comment(String),
+    // Waiting for y to delete it
+    ConfirmDelete,
+}
+
+/*
+What is being typed in the list of sessions
+*/
+#[derive(Clone, Copy)]
+enum Prompt {
+    NewSession,
+    Rename,
+    Import(Format),
+    Export(Format),
+}
+
+impl Prompt {
+    fn label(self) -> String {
+        match self {
+            Prompt::NewSession | Prompt::Rename => "Name".to_string(),
+            Prompt::Import(format) => format!("{} file to import", format.name()),
+            Prompt::Export(format) => format!("Export a {} file to", format.name()),
+        }
+    }
+}
+
 //This struct holds the current state of the app.
-pub struct App<'a> {
-    times: StatefulList<u32>,
-    keybinds: [(&'a str, &'a str); 20],
-    time: i32,
-    timing_status: TimerStatus,
-    ticks_with_no_key: u32,
-    key_released_since_timer_start: bool,
+pub struct App {
+    // The solves of the open session, which are taken out of `sessions` while it is open
+    times: StatefulList<Solve>,
+    stats: Statistics,
+    time_format: TimeFormat,
+    config: Config,
+    theme: Theme,
+    // The setting selected in the settings screen
+    settings_list: ListState,
+    // Why the last change in the settings screen was not made
+    settings_message: Option<String>,
+    // What the last action on the open solve did, when it is not shown otherwise
+    solve_message: Option<String>,
+    history: History,
+    // What the last undo or redo did, shown until the next key
+    message: Option<String>,
+    sessions: Sessions,
+    mode: Mode,
+    // The session selected in the list of sessions
+    session_list: ListState,
+    // Why the last action in the list of sessions did nothing
+    session_message: Option<String>,
+    // The scramble for the next solve, empty while it is being generated
+    scramble: String,
+    scrambles: ScrambleQueue,
+    keymap: Keymap,
+    timer: Timer,
+    timer_key: TimerKey,
 }
 /*
 Set starting values and define functions
 */
-impl<'a> App<'a> {
-    fn new() -> App<'a> {
+impl App {
+    fn new(
+        mut sessions: Sessions,
+        history: History,
+        config: Config,
+        keymap: Keymap,
+        reports_key_releases: bool,
+    ) -> App {
+        let session = &mut sessions.sessions[sessions.active];
+        let solves = mem::take(&mut session.solves);
+        let puzzle = session.puzzle;
         App {
-            times: StatefulList::with_items(vec![
-                1,
-                2,
-                4, // In the wrong order to check if it displays it this way
-                3,
-            ]),
-            keybinds: [
-                ("Quit", "q"),
-                ("Event2", "INFO"),
-                ("Event3", "CRITICAL"),
-                ("Event4", "ERROR"),
-                ("Event5", "INFO"),
-                ("Event6", "INFO"),
-                ("Event7", "WARNING"),
-                ("Event8", "INFO"),
-                ("Event9", "INFO"),
-                ("Event10", "INFO"),
-                ("Event11", "CRITICAL"),
-                ("Event12", "INFO"),
-                ("Event13", "INFO"),
-                ("Event14", "INFO"),
-                ("Event15", "INFO"),
-                ("Event16", "INFO"),
-                ("Event17", "ERROR"),
-                ("Event18", "ERROR"),
-                ("Event19", "INFO"),
-                ("Event20", "INFO"),
-            ],
-            time: 0,
-            timing_status: TimerStatus::PAUSED,
-            ticks_with_no_key: 0,
-            key_released_since_timer_start: false,
-        }
-    }
-
-    pub fn update_timer(&mut self, key_pressed_in_tick: bool) {
-        match self.timing_status {
-            TimerStatus::COUNTDOWN => self.time -= 1,
-            TimerStatus::COUNTUP => self.time += 1,
-            TimerStatus::PAUSED => {}
-        }
-
-        if key_pressed_in_tick == true {
-            if self.timing_status == TimerStatus::PAUSED && self.ticks_with_no_key == 0{
-                self.time = 1500;
-                self.timing_status = TimerStatus::COUNTDOWN;
-            }
-            self.ticks_with_no_key = 0;
-            return
-        }
-        // No key was pressed this tick
-
-        if self.timing_status != TimerStatus::COUNTDOWN {return}
-        // No key was pressed this tick and the timer is counting down
-
-        self.ticks_with_no_key += 1;
-
-        /*
-        We have to wait 600 ms because the termnal receives repeating keys, so if it's pressed again within 600 ms we can assume it is still being held
-        */
-        if self.ticks_with_no_key > 60 {return}
-        // The key was not pressed for 600ms (i.e. The key was released) and the timer is counting down.
-
-        self.ticks_with_no_key = 0;
-        self.time = 0;
-        self.timing_status = TimerStatus::COUNTUP;
+            stats: Statistics::new(&solves, config.phases().len()),
+            times: StatefulList::with_items(solves),
+            time_format: TimeFormat {
+                precision: config.precision as u32,
+            },
+            theme: config.theme(),
+            settings_list: ListState::default(),
+            settings_message: None,
+            solve_message: None,
+            history,
+            message: None,
+            sessions,
+            mode: Mode::Timer,
+            session_list: ListState::default(),
+            session_message: None,
+            scramble: String::new(),
+            scrambles: ScrambleQueue::new(puzzle),
+            keymap,
+            timer: Timer::new(
+                config.hold_time(),
+                config.inspection,
+                config.inspection_time(),
+                config.phases().len(),
+            ),
+            timer_key: TimerKey::new(reports_key_releases, config.release_threshold()),
+            config,
+        }
+    }
+
+    fn session(&self) -> &Session {
+        &self.sessions.sessions[self.sessions.active]
+    }
+
+    /*
+    The selected time, None if nothing is selected or the list is empty
+    */
+    fn selected_solve(&self) -> Option<usize> {
+        self.times
+            .state
+            .selected()
+            .filter(|&index| index < self.times.items.len())
+    }
+
+    fn record_solve(
+        &mut self,
+        time: Duration,
+        penalty: Penalty,
+        splits: Vec<Duration>,
+    ) -> io::Result<()> {
+        let scramble = mem::take(&mut self.scramble);
+        let mut solve = Solve::new(time, penalty, scramble, self.session().puzzle);
+        solve.splits = splits;
+        self.history.record(Edit::AddSolve {
+            session: self.sessions.active,
+            solve: solve.clone(),
+        });
+        self.times.items.push(solve);
+        self.times.state.select(Some(self.times.items.len() - 1));
+        self.solves_changed()
+    }
+
+    fn solves_changed(&mut self) -> io::Result<()> {
+        self.refresh_stats();
+        self.save()
+    }
+
+    fn refresh_stats(&mut self) {
+        self.stats = Statistics::new(&self.times.items, self.config.phases().len());
+    }
+
+    fn save(&mut self) -> io::Result<()> {
+        self.with_all_solves(storage::save_sessions)?;
+        storage::save_history(&self.history)
+    }
+
+    /*
+    Puts the open session's solves back for as long as every session is needed
+    */
+    fn with_all_solves<T>(&mut self, f: impl FnOnce(&Sessions) -> T) -> T {
+        let active = self.sessions.active;
+        mem::swap(
+            &mut self.sessions.sessions[active].solves,
+            &mut self.times.items,
+        );
+        let result = f(&self.sessions);
+        mem::swap(
+            &mut self.sessions.sessions[active].solves,
+            &mut self.times.items,
+        );
+        result
+    }
+
+    /*
+    Opens the session at the index, the solves of the one that was open have to be put back first
+    */
+    fn show_session(&mut self, index: usize, previous_puzzle: Puzzle) {
+        self.sessions.active = index;
+        let solves = mem::take(&mut self.sessions.sessions[index].solves);
+        self.times = StatefulList::with_items(solves);
+        self.refresh_stats();
+        if self.session().puzzle != previous_puzzle {
+            self.restart_scrambles();
+        }
+    }
+
+    fn restart_scrambles(&mut self) {
+        self.scramble.clear();
+        self.scrambles = ScrambleQueue::new(self.session().puzzle);
+    }
+
+    fn open_sessions(&mut self) {
+        self.session_list.select(Some(self.sessions.active));
+        self.mode = Mode::Sessions;
+    }
+
+    fn manual_entry_key(&mut self, code: KeyCode, mut text: String) -> io::Result<()> {
+        match code {
+            KeyCode::Enter => match format::parse_solve(&text) {
+                Some((time, penalty)) => {
+                    self.mode = Mode::Timer;
+                    return self.record_solve(time, penalty, Vec::new());
+                }
+                None => {
+                    self.mode = Mode::ManualEntry {
+                        text,
+                        invalid: true,
+                    }
+                }
+            },
+            KeyCode::Esc => self.mode = Mode::Timer,
+            KeyCode::Backspace => {
+                text.pop();
+                self.mode = Mode::ManualEntry {
+                    text,
+                    invalid: false,
+                };
+            }
+            KeyCode::Char(c) => {
+                text.push(c);
+                self.mode = Mode::ManualEntry {
+                    text,
+                    invalid: false,
+                };
+            }
+            _ => {
+                self.mode = Mode::ManualEntry {
+                    text,
+                    invalid: false,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn settings_key(&mut self, code: KeyCode, editing: Option<String>) -> io::Result<()> {
+        self.settings_message = None;
+        let count = Setting::ALL.len();
+        let selected = self.settings_list.selected().unwrap_or(0);
+        let setting = Setting::ALL[selected];
+        let Some(mut text) = editing else {
+            match code {
+                KeyCode::Esc => return Ok(()),
+                KeyCode::Down => self.settings_list.select(Some((selected + 1) % count)),
+                KeyCode::Up => self
+                    .settings_list
+                    .select(Some((selected + count - 1) % count)),
+                KeyCode::Enter if setting.is_number() => {
+                    self.mode = Mode::Settings {
+                        editing: Some(self.config.value(setting)),
+                    };
+                    return Ok(());
+                }
+                KeyCode::Enter => {
+                    self.config.toggle(setting);
+                    self.config_changed()?;
+                }
+                _ => {}
+            }
+            self.mode = Mode::Settings { editing: None };
+            return Ok(());
+        };
+        match code {
+            KeyCode::Char(c) => text.push(c),
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Enter => match self.config.set(setting, &text) {
+                Ok(()) => {
+                    self.config_changed()?;
+                    self.mode = Mode::Settings { editing: None };
+                    return Ok(());
+                }
+                Err(err) => self.settings_message = Some(err),
+            },
+            KeyCode::Esc => {
+                self.mode = Mode::Settings { editing: None };
+                return Ok(());
+            }
+            _ => {}
+        }
+        self.mode = Mode::Settings {
+            editing: Some(text),
+        };
+        Ok(())
+    }
+
+    /*
+    Passes the settings on to whatever uses them and saves them
+    */
+    fn config_changed(&mut self) -> io::Result<()> {
+        self.time_format.precision = self.config.precision as u32;
+        self.timer.hold_time = self.config.hold_time();
+        self.timer.inspection_enabled = self.config.inspection;
+        self.timer.inspection_time = self.config.inspection_time();
+        self.timer_key.release_threshold = self.config.release_threshold();
+        self.theme = self.config.theme();
+        self.timer.phases = self.config.phases().len();
+        self.refresh_stats();
+        self.config.save()
+    }
+
+    fn open_solve(&mut self) {
+        if let Some(index) = self.selected_solve() {
+            self.solve_message = None;
+            self.mode = Mode::SolveDetails {
+                index,
+                detail: Detail::Viewing,
+            };
+        }
+    }
+
+    fn solve_key(&mut self, code: KeyCode, index: usize, detail: Detail) -> io::Result<()> {
+        self.solve_message = None;
+        let solve = &mut self.times.items[index];
+        let detail = match detail {
+            Detail::Viewing => match code {
+                KeyCode::Esc | KeyCode::Enter => return Ok(()),
+                KeyCode::Char('2') => {
+                    self.toggle_penalty(index, Penalty::PlusTwo)?;
+                    Detail::Viewing
+                }
+                KeyCode::Char('d') => {
+                    self.toggle_penalty(index, Penalty::Dnf)?;
+                    Detail::Viewing
+                }
+                KeyCode::Char('c') => Detail::Comment(solve.comment.clone()),
+                KeyCode::Char('y') => {
+                    clipboard::copy(&solve.scramble)?;
+                    self.solve_message = Some("Copied the scramble".to_string());
+                    Detail::Viewing
+                }
+                KeyCode::Char('x') => Detail::ConfirmDelete,
+                _ => Detail::Viewing,
+            },
+            Detail::Comment(mut text) => match code {
+                KeyCode::Char(c) => {
+                    text.push(c);
+                    Detail::Comment(text)
+                }
+                KeyCode::Backspace => {
+                    text.pop();
+                    Detail::Comment(text)
+                }
+                KeyCode::Enter => {
+                    let before = solve.clone();
+                    solve.comment = text.trim().to_string();
+                    self.history.record(Edit::ChangeSolve {
+                        session: self.sessions.active,
+                        before,
+                        after: solve.clone(),
+                    });
+                    self.mode = Mode::SolveDetails {
+                        index,
+                        detail: Detail::Viewing,
+                    };
+                    return self.solves_changed();
+                }
+                KeyCode::Esc => Detail::Viewing,
+                _ => Detail::Comment(text),
+            },
+            Detail::ConfirmDelete => {
+                if code == KeyCode::Char('y') {
+                    return self.delete_solve(index);
+                }
+                Detail::Viewing
+            }
+        };
+        self.mode = Mode::SolveDetails { index, detail };
+        Ok(())
+    }
+
+    /*
+    Gives the solve the penalty, or takes it off if it already has it
+    */
+    fn toggle_penalty(&mut self, index: usize, penalty: Penalty) -> io::Result<()> {
+        let solve = &mut self.times.items[index];
+        let before = solve.clone();
+        if solve.penalty != penalty {
+            solve.penalty = penalty;
+        } else if solve.time.is_zero() {
+            // A DNF for running out of inspection has no time to go back to
+            self.solve_message = Some("This solve has no time without the DNF".to_string());
+            return Ok(());
+        } else {
+            solve.penalty = Penalty::None;
+        }
+        self.history.record(Edit::ChangeSolve {
+            session: self.sessions.active,
+            before,
+            after: solve.clone(),
+        });
+        self.solves_changed()
+    }
+
+    fn delete_solve(&mut self, index: usize) -> io::Result<()> {
+        let solve = self.times.items.remove(index);
+        self.history.record(Edit::DeleteSolve {
+            session: self.sessions.active,
+            solve,
+        });
+        let count = self.times.items.len();
+        self.times
+            .state
+            .select((count > 0).then(|| index.min(count - 1)));
+        self.solves_changed()
+    }
+
+    /*
+    Undoes or redoes a change from the history, which might be to any session
+    */
+    fn step_history(&mut self, undo: bool) -> io::Result<()> {
+        let previous_puzzle = self.session().puzzle;
+        let selected = self.times.state.selected();
+        let active = self.sessions.active;
+        self.sessions.sessions[active].solves = mem::take(&mut self.times.items);
+        let result = if undo {
+            self.history.undo(&mut self.sessions)
+        } else {
+            self.history.redo(&mut self.sessions)
+        };
+        self.message = Some(result.unwrap_or_else(|err| err));
+        self.show_session(self.sessions.active, previous_puzzle);
+        let count = self.times.items.len();
+        if self.sessions.active == active && count > 0 {
+            self.times
+                .state
+                .select(selected.map(|index| index.min(count - 1)));
+        }
+        self.save()
+    }
+
+    fn session_key(&mut self, code: KeyCode) -> io::Result<()> {
+        self.session_message = None;
+        let selected = self.session_list.selected().unwrap_or(self.sessions.active);
+        let count = self.sessions.sessions.len();
+        match mem::replace(&mut self.mode, Mode::Sessions) {
+            mode @ (Mode::Timer
+            | Mode::ManualEntry { .. }
+            | Mode::Settings { .. }
+            | Mode::SolveDetails { .. }) => self.mode = mode,
+            Mode::Sessions => match code {
+                KeyCode::Esc | KeyCode::Char('s') => self.mode = Mode::Timer,
+                KeyCode::Down => self.session_list.select(Some((selected + 1) % count)),
+                KeyCode::Up => self
+                    .session_list
+                    .select(Some((selected + count - 1) % count)),
+                KeyCode::Enter => {
+                    self.switch_session(selected)?;
+                    self.mode = Mode::Timer;
+                }
+                KeyCode::Char('n') => self.prompt(Prompt::NewSession, String::new()),
+                KeyCode::Char('r') => self.prompt(
+                    Prompt::Rename,
+                    self.sessions.sessions[selected].name.clone(),
+                ),
+                KeyCode::Char('d') if count == 1 => {
+                    self.session_message = Some("The only session cannot be deleted".to_string())
+                }
+                KeyCode::Char('d') => self.mode = Mode::ConfirmDelete,
+                KeyCode::Char('m') => self.merge_session(selected)?,
+                KeyCode::Char('p') => self.change_puzzle(selected)?,
+                KeyCode::Char('i') => self.mode = Mode::ChooseFormat { export: false },
+                KeyCode::Char('e') => self.mode = Mode::ChooseFormat { export: true },
+                _ => {}
+            },
+            Mode::ChooseFormat { export } => {
+                if let Some(format) = key_char(code).and_then(Format::from_key) {
+                    let prompt = if export {
+                        Prompt::Export(format)
+                    } else {
+                        Prompt::Import(format)
+                    };
+                    self.prompt(prompt, String::new());
+                }
+            }
+            Mode::Prompt { prompt, mut text } => match code {
+                KeyCode::Char(c) => {
+                    text.push(c);
+                    self.prompt(prompt, text);
+                }
+                KeyCode::Backspace => {
+                    text.pop();
+                    self.prompt(prompt, text);
+                }
+                KeyCode::Enter if !text.trim().is_empty() => {
+                    self.answer(prompt, text.trim().to_string(), selected)?
+                }
+                KeyCode::Esc => {}
+                _ => self.prompt(prompt, text),
+            },
+            Mode::ConfirmDelete => {
+                if code == KeyCode::Char('y') {
+                    self.delete_session(selected)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn prompt(&mut self, prompt: Prompt, text: String) {
+        self.mode = Mode::Prompt { prompt, text };
+    }
+
+    fn answer(&mut self, prompt: Prompt, text: String, selected: usize) -> io::Result<()> {
+        match prompt {
+            Prompt::NewSession => {
+                // New sessions start with the puzzle of the selected one
+                let puzzle = self.sessions.sessions[selected].puzzle;
+                self.sessions.sessions.push(Session::new(text, puzzle));
+                self.session_list
+                    .select(Some(self.sessions.sessions.len() - 1));
+            }
+            Prompt::Rename => {
+                let before = mem::replace(&mut self.sessions.sessions[selected].name, text.clone());
+                self.history.record(Edit::RenameSession {
+                    index: selected,
+                    before,
+                    after: text,
+                });
+            }
+            Prompt::Import(format) => {
+                // A file that cannot be read is reported rather than quitting
+                let path = formats::expand_home(&text);
+                self.session_message = Some(match self.import(format, &path, selected) {
+                    Ok(message) => message,
+                    Err(err) => format!("Could not import: {err}"),
+                });
+            }
+            Prompt::Export(format) => {
+                let path = formats::expand_home(&text);
+                let result = self.with_all_solves(|sessions| match format {
+                    Format::CsTimer => formats::cstimer::export(&path, &sessions.sessions),
+                    Format::TwistyTimer => {
+                        formats::twisty_timer::export(&path, &sessions.sessions[selected])
+                    }
+                    Format::CubeDesk => {
+                        formats::cubedesk::export(&path, &sessions.sessions[selected])
+                    }
+                });
+                self.session_message = Some(match result {
+                    Ok(()) => format!("Exported to {}", path.display()),
+                    Err(err) => format!("Could not export: {err}"),
+                });
+                return Ok(());
+            }
+        }
+        self.save()
+    }
+
+    /*
+    csTimer backups are added as new sessions, the solves in other files go into the selected session unless it already has them
+    */
+    fn import(&mut self, format: Format, path: &Path, selected: usize) -> io::Result<String> {
+        let puzzle = self.sessions.sessions[selected].puzzle;
+        let solves = match format {
+            Format::CsTimer => {
+                let first_created = self.sessions.sessions.len();
+                let mut added = Vec::new();
+                for session in formats::cstimer::import(path)? {
+                    // Sessions imported before get only the solves they do not already have
+                    let existing =
+                        self.sessions.sessions[..first_created]
+                            .iter()
+                            .position(|existing| {
+                                existing.name == session.name && existing.puzzle == session.puzzle
+                            });
+                    match existing {
+                        Some(index) => added.push((index, self.add_solves(index, session.solves))),
+                        None => self.sessions.sessions.push(session),
+                    }
+                }
+                let created = self.sessions.sessions[first_created..].to_vec();
+                let count = added.iter().map(|(_, solves)| solves.len()).sum::<usize>()
+                    + created
+                        .iter()
+                        .map(|session| session.solves.len())
+                        .sum::<usize>();
+                let message = format!(
+                    "Imported {count} solves, making {} new sessions",
+                    created.len()
+                );
+                if count > 0 || !created.is_empty() {
+                    self.history.record(Edit::Import {
+                        added,
+                        first_created,
+                        created,
+                    });
+                }
+                return Ok(message);
+            }
+            Format::TwistyTimer => formats::twisty_timer::import(path, puzzle)?,
+            Format::CubeDesk => formats::cubedesk::import(path, puzzle)?,
+        };
+        let found = solves.len();
+        let added = self.add_solves(selected, solves);
+        let message = format!(
+            "Imported {} {} solves, {} were already in the session",
+            added.len(),
+            puzzle.name(),
+            found - added.len()
+        );
+        if !added.is_empty() {
+            self.history.record(Edit::Import {
+                added: vec![(selected, added)],
+                first_created: self.sessions.sessions.len(),
+                created: Vec::new(),
+            });
+        }
+        Ok(message)
+    }
+
+    /*
+    Adds the solves the session does not already have, returning the ones that were added
+    */
+    fn add_solves(&mut self, index: usize, solves: Vec<Solve>) -> Vec<Solve> {
+        if index != self.sessions.active {
+            return session::add_solves(&mut self.sessions.sessions[index].solves, solves);
+        }
+        let added = session::add_solves(&mut self.times.items, solves);
+        self.times.unselect();
+        self.refresh_stats();
+        added
+    }
+
+    fn switch_session(&mut self, index: usize) -> io::Result<()> {
+        if index == self.sessions.active {
+            return Ok(());
+        }
+        let previous_puzzle = self.session().puzzle;
+        let active = self.sessions.active;
+        self.sessions.sessions[active].solves = mem::take(&mut self.times.items);
+        self.show_session(index, previous_puzzle);
+        self.save()
+    }
+
+    fn delete_session(&mut self, index: usize) -> io::Result<()> {
+        let previous_puzzle = self.session().puzzle;
+        let mut session = self.sessions.sessions.remove(index);
+        if index == self.sessions.active {
+            session.solves = mem::take(&mut self.times.items);
+        }
+        self.history.record(Edit::DeleteSession { index, session });
+        let count = self.sessions.sessions.len();
+        if index < self.sessions.active {
+            self.sessions.active -= 1;
+        } else if index == self.sessions.active {
+            // The open session's solves go with it
+            self.show_session(index.min(count - 1), previous_puzzle);
+        }
+        self.session_list.select(Some(index.min(count - 1)));
+        self.save()
+    }
+
+    /*
+    Moves the solves of the session at the index into the open one
+    */
+    fn merge_session(&mut self, index: usize) -> io::Result<()> {
+        if index == self.sessions.active {
+            self.session_message =
+                Some("Select another session to merge into this one".to_string());
+            return Ok(());
+        }
+        if self.sessions.sessions[index].puzzle != self.session().puzzle {
+            self.session_message =
+                Some("Only sessions of the same puzzle can be merged".to_string());
+            return Ok(());
+        }
+        let into = self.sessions.active;
+        let session = self.sessions.remove(index);
+        self.session_list.select(Some(self.sessions.active));
+        let added = session::add_solves(&mut self.times.items, session.solves.clone());
+        self.history.record(Edit::MergeSessions {
+            merged: index,
+            into,
+            session,
+            added,
+        });
+        self.times.unselect();
+        self.solves_changed()
+    }
+
+    /*
+    Sessions are tied to their puzzle once they have solves, so only empty ones can change it
+    */
+    fn change_puzzle(&mut self, index: usize) -> io::Result<()> {
+        let is_active = index == self.sessions.active;
+        let is_empty = if is_active {
+            self.times.items.is_empty()
+        } else {
+            self.sessions.sessions[index].solves.is_empty()
+        };
+        if !is_empty {
+            self.session_message = Some("Only empty sessions can change puzzle".to_string());
+            return Ok(());
+        }
+        let session = &mut self.sessions.sessions[index];
+        session.puzzle = session.puzzle.next();
+        if is_active {
+            self.restart_scrambles();
+        }
+        self.save()
+    }
+}
+
+fn key_char(code: KeyCode) -> Option<char> {
+    match code {
+        KeyCode::Char(c) => Some(c),
+        _ => None,
     }
 }
 
@@ -158,19 +843,43 @@ impl<'a> App<'a> {
 Setup, run the program and cleanup
 */
 fn main() -> Result<(), Box<dyn Error>> {
+    let sessions = storage::load_sessions()?;
+    let history = storage::load_history()?;
+    // A mistake in the config files is reported before the terminal is taken over
+    let loaded = Config::load().and_then(|config| Ok((config, Keymap::load()?)));
+    let (config, keymap) = match loaded {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            eprintln!("{err}");
+            process::exit(1);
+        }
+    };
+
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen)?;
+    // Ask for real key release events where the terminal supports them
+    let keyboard_enhancement = supports_keyboard_enhancement().unwrap_or(false);
+    if keyboard_enhancement {
+        execute!(
+            stdout,
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+        )?;
+    }
+    // The Windows console always reports releases
+    let reports_key_releases = keyboard_enhancement || cfg!(windows);
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     // create app and run it
-    let tick_rate = Duration::from_millis(10);
-    let app = App::new();
-    let res = run_app(&mut terminal, app, tick_rate);
+    let app = App::new(sessions, history, config, keymap, reports_key_releases);
+    let res = run_app(&mut terminal, app);
 
     // restore terminal
+    if keyboard_enhancement {
+        execute!(terminal.backend_mut(), PopKeyboardEnhancementFlags)?;
+    }
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
@@ -189,36 +898,124 @@ fn main() -> Result<(), Box<dyn Error>> {
 /*
 Main loop
 */
-fn run_app<B: Backend>(
-    terminal: &mut Terminal<B>,
-    mut app: App,
-    tick_rate: Duration,
-) -> io::Result<()> {
+fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<()> {
     let mut last_tick = Instant::now();
-    let mut timer_key_pressed_in_tick = false;
     loop {
         terminal.draw(|f| ui::draw(f, &mut app))?;
 
+        // Read every time round as it can be changed in the settings
+        let tick_rate = app.config.tick_rate();
         let timeout = tick_rate
             .checked_sub(last_tick.elapsed())
             .unwrap_or_else(|| Duration::from_secs(0));
         if crossterm::event::poll(timeout)? {
             if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char(' ') => timer_key_pressed_in_tick = true,
-                    KeyCode::Char('q') => return Ok(()),
-                    KeyCode::Left => app.times.unselect(),
-                    KeyCode::Down => app.times.next(),
-                    KeyCode::Up => app.times.previous(),
+                let now = Instant::now();
+                let action = app.keymap.action(&key);
+                if key.kind == KeyEventKind::Release {
+                    if action == Some(Action::Timer) {
+                        app.timer_key.release();
+                        app.timer.key_up(now);
+                    }
+                    continue;
+                }
+                match mem::replace(&mut app.mode, Mode::Timer) {
+                    Mode::Timer => app.message = None,
+                    Mode::ManualEntry { text, .. } => {
+                        app.manual_entry_key(key.code, text)?;
+                        continue;
+                    }
+                    Mode::Settings { editing } => {
+                        app.settings_key(key.code, editing)?;
+                        continue;
+                    }
+                    Mode::SolveDetails { index, detail } => {
+                        app.solve_key(key.code, index, detail)?;
+                        continue;
+                    }
+                    mode => {
+                        app.mode = mode;
+                        app.session_key(key.code)?;
+                        continue;
+                    }
+                }
+                if app.timer.status == TimerStatus::Countup {
+                    /*
+                    The timer key ends each phase of a split solve and any key stops it. Without release events, presses closer together than the release threshold look like the key being held and are ignored
+                    */
+                    if action == Some(Action::Timer)
+                        && (!app.timer_key.press(now) || app.timer.split(now))
+                    {
+                        continue;
+                    }
+                    if let Some((time, penalty)) = app.timer.stop(now) {
+                        let splits = app.timer.splits().to_vec();
+                        app.record_solve(time, penalty, splits)?;
+                    }
+                    continue;
+                }
+                let paused = app.timer.status == TimerStatus::Paused;
+                match action {
+                    Some(Action::Timer) if app.timer_key.press(now) => app.timer.key_down(now),
+                    Some(Action::Quit) => return Ok(()),
+                    Some(Action::Sessions) if paused => app.open_sessions(),
+                    Some(Action::Settings) if paused => {
+                        app.settings_list.select(Some(0));
+                        app.mode = Mode::Settings { editing: None };
+                    }
+                    Some(Action::NextSession) if paused => {
+                        let next = (app.sessions.active + 1) % app.sessions.sessions.len();
+                        app.switch_session(next)?;
+                    }
+                    Some(Action::PreviousSession) if paused => {
+                        let count = app.sessions.sessions.len();
+                        app.switch_session((app.sessions.active + count - 1) % count)?;
+                    }
+                    Some(Action::EnterTime) if paused => {
+                        app.mode = Mode::ManualEntry {
+                            text: String::new(),
+                            invalid: false,
+                        }
+                    }
+                    Some(Action::SolveDetails) if paused => app.open_solve(),
+                    Some(Action::PlusTwo) if paused => {
+                        if let Some(index) = app.selected_solve() {
+                            app.toggle_penalty(index, Penalty::PlusTwo)?;
+                        }
+                    }
+                    Some(Action::Dnf) if paused => {
+                        if let Some(index) = app.selected_solve() {
+                            app.toggle_penalty(index, Penalty::Dnf)?;
+                        }
+                    }
+                    Some(Action::DeleteSolve) if paused => {
+                        if let Some(index) = app.selected_solve() {
+                            app.mode = Mode::SolveDetails {
+                                index,
+                                detail: Detail::ConfirmDelete,
+                            };
+                        }
+                    }
+                    Some(Action::Undo) if paused => app.step_history(true)?,
+                    Some(Action::Redo) if paused => app.step_history(false)?,
+                    Some(Action::Unselect) => app.times.unselect(),
+                    Some(Action::NextSolve) => app.times.next(),
+                    Some(Action::PreviousSolve) => app.times.previous(),
                     _ => {}
                 }
             }
         }
         if last_tick.elapsed() >= tick_rate {
             last_tick = Instant::now();
-
-            app.update_timer(timer_key_pressed_in_tick);
-            timer_key_pressed_in_tick = false;
+            if app.scramble.is_empty() {
+                app.scramble = app.scrambles.try_next().unwrap_or_default();
+            }
+            if let Some((time, penalty)) = app.timer.update(last_tick) {
+                app.record_solve(time, penalty, Vec::new())?;
+            }
+            if let Some(released_at) = app.timer_key.check_release(last_tick) {
+                app.timer.key_up(released_at);
+            }
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,47 @@
+/*
+Solving methods and the phases they split a solve into
+
+With a method chosen, each press of the timer key during a solve ends a phase until the last one, which stops the timer. Methods of the user's own are lists of phase names under `[methods]` in the config.
+*/
+
+use std::collections::BTreeMap;
+
+// "none" times solves without splits
+const BUILT_IN: [(&str, &[&str]); 4] = [
+    ("none", &[]),
+    ("cfop", &["Cross", "F2L", "OLL", "PLL"]),
+    ("roux", &["First block", "Second block", "CMLL", "LSE"]),
+    ("zz", &["EOLine", "F2L", "LL"]),
+];
+
+// The user's methods by name, each the names of its phases in order
+pub type Methods = BTreeMap<String, Vec<String>>;
+
+/*
+The phases of the method with the name, looking at the user's methods before the built-in ones
+*/
+pub fn phases(name: &str, methods: &Methods) -> Result<Vec<String>, String> {
+    if let Some(phases) = methods.get(name) {
+        if phases.len() < 2 {
+            return Err(format!("Method \"{name}\" should have at least 2 phases"));
+        }
+        return Ok(phases.clone());
+    }
+    BUILT_IN
+        .iter()
+        .find(|&&(built_in, _)| built_in == name)
+        .map(|(_, phases)| phases.iter().map(|phase| phase.to_string()).collect())
+        .ok_or_else(|| format!("There is no method \"{name}\""))
+}
+
+/*
+The names of every method, the built-in ones first
+*/
+pub fn names(methods: &Methods) -> Vec<&str> {
+    let built_in = BUILT_IN.iter().map(|&(name, _)| name);
+    let own = methods
+        .keys()
+        .map(String::as_str)
+        .filter(|name| !BUILT_IN.iter().any(|(built_in, _)| built_in == name));
+    built_in.chain(own).collect()
+}
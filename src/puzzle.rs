@@ -0,0 +1,113 @@
+/*
+The puzzles that can be timed
+*/
+
+use serde::{Deserialize, Serialize};
+
+// Stored with the WCA event IDs
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Puzzle {
+    #[serde(rename = "222")]
+    TwoByTwo,
+    #[serde(rename = "333")]
+    ThreeByThree,
+    #[serde(rename = "444")]
+    FourByFour,
+    #[serde(rename = "555")]
+    FiveByFive,
+    #[serde(rename = "666")]
+    SixBySix,
+    #[serde(rename = "777")]
+    SevenBySeven,
+    #[serde(rename = "pyram")]
+    Pyraminx,
+    #[serde(rename = "skewb")]
+    Skewb,
+    #[serde(rename = "minx")]
+    Megaminx,
+    #[serde(rename = "sq1")]
+    Square1,
+    #[serde(rename = "clock")]
+    Clock,
+}
+
+impl Puzzle {
+    pub const ALL: [Puzzle; 11] = [
+        Puzzle::TwoByTwo,
+        Puzzle::ThreeByThree,
+        Puzzle::FourByFour,
+        Puzzle::FiveByFive,
+        Puzzle::SixBySix,
+        Puzzle::SevenBySeven,
+        Puzzle::Pyraminx,
+        Puzzle::Skewb,
+        Puzzle::Megaminx,
+        Puzzle::Square1,
+        Puzzle::Clock,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Puzzle::TwoByTwo => "2x2",
+            Puzzle::ThreeByThree => "3x3",
+            Puzzle::FourByFour => "4x4",
+            Puzzle::FiveByFive => "5x5",
+            Puzzle::SixBySix => "6x6",
+            Puzzle::SevenBySeven => "7x7",
+            Puzzle::Pyraminx => "Pyraminx",
+            Puzzle::Skewb => "Skewb",
+            Puzzle::Megaminx => "Megaminx",
+            Puzzle::Square1 => "Square-1",
+            Puzzle::Clock => "Clock",
+        }
+    }
+
+    /*
+    The WCA event ID, the same as it is stored with
+    */
+    pub fn id(self) -> &'static str {
+        match self {
+            Puzzle::TwoByTwo => "222",
+            Puzzle::ThreeByThree => "333",
+            Puzzle::FourByFour => "444",
+            Puzzle::FiveByFive => "555",
+            Puzzle::SixBySix => "666",
+            Puzzle::SevenBySeven => "777",
+            Puzzle::Pyraminx => "pyram",
+            Puzzle::Skewb => "skewb",
+            Puzzle::Megaminx => "minx",
+            Puzzle::Square1 => "sq1",
+            Puzzle::Clock => "clock",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Puzzle> {
+        Puzzle::ALL.into_iter().find(|puzzle| puzzle.id() == id)
+    }
+
+    /*
+    The number of layers for NxN cubes, None for other puzzles
+    */
+    pub fn cube_size(self) -> Option<usize> {
+        match self {
+            Puzzle::TwoByTwo => Some(2),
+            Puzzle::ThreeByThree => Some(3),
+            Puzzle::FourByFour => Some(4),
+            Puzzle::FiveByFive => Some(5),
+            Puzzle::SixBySix => Some(6),
+            Puzzle::SevenBySeven => Some(7),
+            _ => None,
+        }
+    }
+
+    /*
+    The puzzle after this one in the selector, wrapping around
+    */
+    pub fn next(self) -> Puzzle {
+        let index = Puzzle::ALL
+            .iter()
+            .position(|&puzzle| puzzle == self)
+            .unwrap();
+        Puzzle::ALL[(index + 1) % Puzzle::ALL.len()]
+    }
+}
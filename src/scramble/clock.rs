@@ -0,0 +1,30 @@
+/*
+Random-state Clock scrambles in WCA notation
+
+Every pin configuration is turned by a random amount and the pins left up at the end are random too.
+*/
+
+use rand::Rng;
+
+const FRONT_TURNS: [&str; 9] = ["UR", "DR", "DL", "UL", "U", "R", "D", "L", "ALL"];
+const BACK_TURNS: [&str; 5] = ["U", "R", "D", "L", "ALL"];
+const PINS: [&str; 4] = ["UR", "DR", "DL", "UL"];
+
+pub fn clock() -> String {
+    let mut rng = rand::thread_rng();
+    let mut turn = |pins: &str| {
+        // From 5 back to 6 forward, 6 either way being the same
+        let amount: i32 = rng.gen_range(-5..=6);
+        let direction = if amount < 0 { '-' } else { '+' };
+        format!("{pins}{}{direction}", amount.abs())
+    };
+    let mut moves: Vec<String> = FRONT_TURNS.iter().map(|pins| turn(pins)).collect();
+    moves.push("y2".to_string());
+    moves.extend(BACK_TURNS.iter().map(|pins| turn(pins)));
+    moves.extend(
+        PINS.iter()
+            .filter(|_| rng.gen())
+            .map(|pins| pins.to_string()),
+    );
+    moves.join(" ")
+}
@@ -0,0 +1,20 @@
+/*
+Megaminx scrambles in Pochmann notation, seven lines of ten R and D moves, each line followed by a U move
+*/
+
+use rand::Rng;
+
+pub fn megaminx() -> String {
+    let mut rng = rand::thread_rng();
+    let mut moves = Vec::new();
+    for _ in 0..7 {
+        let mut clockwise = false;
+        for face in ["R", "D"].iter().cycle().take(10) {
+            clockwise = rng.gen();
+            moves.push(format!("{face}{}", if clockwise { "++" } else { "--" }));
+        }
+        // The U move goes the same way as the last D move
+        moves.push(if clockwise { "U" } else { "U'" }.to_string());
+    }
+    moves.join(" ")
+}
@@ -0,0 +1,71 @@
+/*
+Scramble generation for every WCA puzzle
+*/
+
+mod clock;
+mod megaminx;
+mod nxn;
+mod pyraminx;
+mod skewb;
+mod square1;
+mod sticker_puzzle;
+
+use crate::{
+    puzzle::Puzzle,
+    two_phase::{self, Tables},
+};
+use std::{
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+/*
+Scrambles generated ahead of time on another thread, so the next one is ready before the current solve finishes
+*/
+pub struct ScrambleQueue {
+    receiver: Receiver<String>,
+}
+
+impl ScrambleQueue {
+    pub fn new(puzzle: Puzzle) -> ScrambleQueue {
+        // One scramble waits in the channel while the next is generated
+        let (sender, receiver) = mpsc::sync_channel(1);
+        // The thread stops once the queue is dropped for another puzzle
+        thread::spawn(move || {
+            if puzzle == Puzzle::ThreeByThree {
+                let tables = Tables::load();
+                while sender
+                    .send(two_phase::random_state_scramble(&tables))
+                    .is_ok()
+                {}
+            } else if puzzle == Puzzle::Square1 {
+                let tables = square1::Tables::new();
+                while sender.send(square1::square_1(&tables)).is_ok() {}
+            } else {
+                while sender.send(generate(puzzle)).is_ok() {}
+            }
+        });
+        ScrambleQueue { receiver }
+    }
+
+    /*
+    The next scramble if one is ready
+    */
+    pub fn try_next(&self) -> Option<String> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+/*
+Everything but the 3x3 and Square-1, which need their tables first
+*/
+fn generate(puzzle: Puzzle) -> String {
+    match puzzle {
+        Puzzle::TwoByTwo => nxn::two_by_two(),
+        Puzzle::Pyraminx => pyraminx::pyraminx(),
+        Puzzle::Skewb => skewb::skewb(),
+        Puzzle::Megaminx => megaminx::megaminx(),
+        Puzzle::Clock => clock::clock(),
+        _ => nxn::big_cube(puzzle.cube_size().unwrap()),
+    }
+}
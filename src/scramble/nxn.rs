@@ -0,0 +1,68 @@
+/*
+Scrambles for the NxN cubes other than the 3x3
+*/
+
+use super::sticker_puzzle::StickerPuzzle;
+use crate::cube::{FaceletCube, Move};
+use rand::Rng;
+
+const FACE_NAMES: [&str; 6] = ["U", "R", "F", "D", "L", "B"];
+const POWER_NAMES: [&str; 3] = ["", "2", "'"];
+
+/*
+Random state, solved with R, U and F so the DBL corner stays where it is
+*/
+pub fn two_by_two() -> String {
+    let cube = FaceletCube::solved(2);
+    let turns = ["R", "U", "F"]
+        .iter()
+        .map(|&name| {
+            let m = Move::parse(name, 2).unwrap();
+            (name, cube.move_permutation(&m), 4)
+        })
+        .collect();
+    let solved = cube.facelets().iter().map(|&face| face as u8).collect();
+    // Like the WCA scrambler, states that are too close to solved are skipped
+    StickerPuzzle::new(solved, turns).random_state_scramble(4)
+}
+
+/*
+Random moves in WCA notation, with the number of moves the WCA uses for each size
+*/
+pub fn big_cube(size: usize) -> String {
+    let length = match size {
+        4 => 40,
+        5 => 60,
+        6 => 80,
+        _ => 100,
+    };
+    let mut rng = rand::thread_rng();
+    let mut moves = Vec::with_capacity(length);
+    // The face and depth of the moves since the axis last changed, none of which can be repeated without cancelling
+    let mut same_axis: Vec<(usize, usize)> = Vec::new();
+    while moves.len() < length {
+        let face = rng.gen_range(0..6);
+        let depth = rng.gen_range(1..=size / 2);
+        if same_axis
+            .first()
+            .is_some_and(|&(first, _)| first % 3 != face % 3)
+        {
+            same_axis.clear();
+        }
+        if same_axis.contains(&(face, depth)) {
+            continue;
+        }
+        same_axis.push((face, depth));
+        let prefix = match depth {
+            1 | 2 => String::new(),
+            depth => depth.to_string(),
+        };
+        let wide = if depth > 1 { "w" } else { "" };
+        moves.push(format!(
+            "{prefix}{}{wide}{}",
+            FACE_NAMES[face],
+            POWER_NAMES[rng.gen_range(0..3)]
+        ));
+    }
+    moves.join(" ")
+}
@@ -0,0 +1,63 @@
+/*
+Random-state Pyraminx scrambles
+
+The puzzle is held with a face at the front, so U is the top corner, L and R the front corners and B the back corner.
+*/
+
+use super::sticker_puzzle::{Point, StickerPuzzle};
+use rand::Rng;
+
+const CORNERS: [(&str, &str); 4] = [("U", "u"), ("L", "l"), ("R", "r"), ("B", "b")];
+
+pub fn pyraminx() -> String {
+    // A regular tetrahedron with edges of length 1 centred on the origin
+    let height = (2.0f64 / 3.0).sqrt();
+    let radius = 1.0 / 3.0f64.sqrt();
+    let base = -height / 4.0;
+    let vertices: [Point; 4] = [
+        [0.0, height + base, 0.0],
+        [-0.5, base, radius / 2.0],
+        [0.5, base, radius / 2.0],
+        [0.0, base, -radius],
+    ];
+    // The front, right, left and bottom faces by their corners
+    let faces = [[0, 1, 2], [0, 2, 3], [0, 3, 1], [1, 3, 2]];
+
+    // Every face is split into nine triangles, the tips are left out as they are scrambled separately
+    let mut stickers = Vec::new();
+    for (colour, corners) in faces.iter().enumerate() {
+        let weights = [[4.0, 4.0, 1.0], [2.0, 5.0, 2.0]];
+        for weight in weights {
+            for rotation in 0..3 {
+                let position = [0, 1, 2].map(|axis| {
+                    (0..3)
+                        .map(|i| weight[(i + rotation) % 3] * vertices[corners[i]][axis])
+                        .sum::<f64>()
+                        / 9.0
+                });
+                stickers.push((position, colour as u8));
+            }
+        }
+    }
+
+    // The cut is a third of the way from the opposite face to the corner
+    let circumradius = (height + base).abs();
+    let turns: Vec<(&str, Point, f64)> = CORNERS
+        .iter()
+        .zip(vertices)
+        .map(|(&(name, _), vertex)| (name, vertex, circumradius / 9.0))
+        .collect();
+    // WCA scrambles are at least six moves long, not counting the tips
+    let puzzle = StickerPuzzle::from_geometry(&stickers, &turns, 3);
+    let mut scramble = puzzle.random_state_scramble(6);
+
+    let mut rng = rand::thread_rng();
+    for (_, tip) in CORNERS {
+        match rng.gen_range(0..3) {
+            1 => scramble.push_str(&format!(" {tip}")),
+            2 => scramble.push_str(&format!(" {tip}'")),
+            _ => {}
+        }
+    }
+    scramble
+}
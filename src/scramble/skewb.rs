@@ -0,0 +1,36 @@
+/*
+Random-state Skewb scrambles
+
+In WCA notation R turns the DRB corner, U the ULB corner, L the DLF corner and B the DBL corner, so the UFR corner never moves.
+*/
+
+use super::sticker_puzzle::{Point, StickerPuzzle};
+
+pub fn skewb() -> String {
+    // Every face has a centre and four corner stickers
+    let mut stickers = Vec::new();
+    for axis in 0..3 {
+        for sign in [1.0, -1.0] {
+            let colour = (axis * 2) as u8 + (sign < 0.0) as u8;
+            let mut centre = [0.0; 3];
+            centre[axis] = sign;
+            stickers.push((centre, colour));
+            for (first, second) in [(1.0, 1.0), (1.0, -1.0), (-1.0, 1.0), (-1.0, -1.0)] {
+                let mut corner = centre;
+                corner[(axis + 1) % 3] = 0.6 * first;
+                corner[(axis + 2) % 3] = 0.6 * second;
+                stickers.push((corner, colour));
+            }
+        }
+    }
+
+    let turns: [(&str, Point, f64); 4] = [
+        ("R", [1.0, -1.0, -1.0], 0.0),
+        ("U", [-1.0, 1.0, -1.0], 0.0),
+        ("L", [-1.0, -1.0, 1.0], 0.0),
+        ("B", [-1.0, -1.0, -1.0], 0.0),
+    ];
+    // WCA scrambles are at least seven moves long
+    let puzzle = StickerPuzzle::from_geometry(&stickers, &turns, 3);
+    puzzle.random_state_scramble(7)
+}
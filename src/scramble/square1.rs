@@ -0,0 +1,507 @@
+/*
+Random-state Square-1 scrambles in WCA notation
+
+The layers are tracked as twelve 30 degree slots, each holding the piece that covers it, corners taking two slots and edges one. Each layer's slots are counted in the direction that layer turns, so the slice swaps slots 0 to 5 of the top with slots 6 to 11 of the bottom.
+
+A random shape the slice can turn from is picked, each being as likely as it is among all states as every shape holds the pieces in as many ways, and the pieces are shuffled into it. That state is solved in two phases, first back to the shape of a cube with the parity the second phase can solve, then to solved with only the turns and slices that keep it a cube. The scramble is the solution undone.
+*/
+
+use rand::{seq::SliceRandom, Rng};
+use std::collections::{HashMap, VecDeque};
+
+// Corners are pieces 0 to 7 and edges 8 to 15
+const SOLVED: Square1 = Square1 {
+    top: [0, 0, 8, 1, 1, 9, 2, 2, 10, 3, 3, 11],
+    bottom: [12, 4, 4, 13, 5, 5, 14, 6, 6, 15, 7, 7],
+    middle_flipped: false,
+};
+// The slots that start a piece on a square layer with a corner at slot 0
+const SQUARE: u16 = 0b1011_0110_1101;
+// 8! orders of the corners, and of the edges with the middle either way round, each with the offsets of both layers
+const N_CORNERS: usize = 40320 * 4;
+const N_EDGES: usize = 40320 * 2 * 4;
+const UNVISITED: u8 = u8::MAX;
+// The cubes this many slices or fewer from solved have their distances stored exactly
+const NEAR: u8 = 3;
+// How many cubes the second phase looks at before trying another way to the shape of a cube
+const NODE_LIMIT: u32 = 5_000;
+
+// The shapes of the top and bottom with the parity of a state
+type Node = ((u16, u16), bool);
+
+#[derive(Clone, Copy, PartialEq)]
+struct Square1 {
+    top: [u8; 12],
+    bottom: [u8; 12],
+    middle_flipped: bool,
+}
+
+impl Square1 {
+    fn turn(&self, top: usize, bottom: usize) -> Square1 {
+        let mut turned = *self;
+        turned.top.rotate_right(top % 12);
+        turned.bottom.rotate_right(bottom % 12);
+        turned
+    }
+
+    /*
+    None if a piece is in the way
+    */
+    fn slice(&self) -> Option<Square1> {
+        if !can_slice(&self.top) || !can_slice(&self.bottom) {
+            return None;
+        }
+        let mut sliced = *self;
+        for i in 0..6 {
+            std::mem::swap(&mut sliced.top[i], &mut sliced.bottom[i + 6]);
+        }
+        sliced.middle_flipped = !sliced.middle_flipped;
+        Some(sliced)
+    }
+
+    fn node(&self) -> Node {
+        ((shape(&self.top), shape(&self.bottom)), self.parity())
+    }
+
+    /*
+    Whether the corners and the edges, read slot by slot from the top to the bottom, are in orders of different parity, for states the slice can turn from
+    */
+    fn parity(&self) -> bool {
+        let mut pieces = Vec::with_capacity(16);
+        for layer in [&self.top, &self.bottom] {
+            for (slot, &piece) in layer.iter().enumerate() {
+                // A corner is read at its first slot
+                if slot == 0 || layer[slot - 1] != piece {
+                    pieces.push(piece);
+                }
+            }
+        }
+        let mut odd = false;
+        for (i, &piece) in pieces.iter().enumerate() {
+            for &later in &pieces[i + 1..] {
+                if (piece < 8) == (later < 8) && piece > later {
+                    odd = !odd;
+                }
+            }
+        }
+        odd
+    }
+
+    /*
+    A state of the shape with the pieces numbered in the order they are read, which has even parity
+    */
+    fn with_shape((top, bottom): (u16, u16)) -> Square1 {
+        let mut corners = 0..8;
+        let mut edges = 8..16;
+        let mut fill = |shape: u16| {
+            let mut layer = [0; 12];
+            for slot in 0..12 {
+                layer[slot] = if shape & 1 << slot == 0 {
+                    layer[slot - 1]
+                } else if shape & 1 << ((slot + 1) % 12) == 0 {
+                    corners.next().unwrap_or_default()
+                } else {
+                    edges.next().unwrap_or_default()
+                };
+            }
+            layer
+        };
+        Square1 {
+            top: fill(top),
+            bottom: fill(bottom),
+            middle_flipped: false,
+        }
+    }
+}
+
+/*
+A state in the shape of a cube, with the corners and edges of each layer in the order they are read and how many slots each layer is turned past having a corner at slot 0
+
+A layer can only be sliced through when it is turned 0 or 1 slots past, and slicing keeps the shape when both layers are turned the same.
+Turns change the order the corners and edges are read in as well as the offset, so the parity of a cube is its order's parity with a change for each layer turned 1 slot past, which no turn or slice changes.
+*/
+#[derive(Clone, Copy, PartialEq)]
+struct Cube {
+    corners: [[u8; 4]; 2],
+    edges: [[u8; 4]; 2],
+    offsets: [usize; 2],
+    middle_flipped: bool,
+}
+
+impl Cube {
+    fn new(state: &Square1) -> Option<Cube> {
+        let mut cube = Cube {
+            corners: [[0; 4]; 2],
+            edges: [[0; 4]; 2],
+            offsets: [offset(shape(&state.top))?, offset(shape(&state.bottom))?],
+            middle_flipped: state.middle_flipped,
+        };
+        for (layer, slots) in [state.top, state.bottom].iter().enumerate() {
+            let offset = cube.offsets[layer];
+            for i in 0..4 {
+                cube.corners[layer][i] = slots[3 * i + offset];
+                cube.edges[layer][i] = slots[(3 * i + [2, 0, 1][offset]) % 12];
+            }
+        }
+        Some(cube)
+    }
+
+    fn turn(&self, top: usize, bottom: usize) -> Cube {
+        let mut turned = *self;
+        for (layer, amount) in [top, bottom].into_iter().enumerate() {
+            let offset = self.offsets[layer];
+            // Corners are read from a new slot when one crosses slot 0, and edges likewise
+            turned.corners[layer].rotate_right((offset + amount) / 3 % 4);
+            turned.edges[layer]
+                .rotate_right(((offset + amount).div_ceil(3) - offset.div_ceil(3)) % 4);
+            turned.offsets[layer] = (offset + amount) % 3;
+        }
+        turned
+    }
+
+    fn slice(&self) -> Cube {
+        let mut sliced = *self;
+        for pieces in [&mut sliced.corners, &mut sliced.edges] {
+            let [top, bottom] = pieces;
+            std::mem::swap(&mut top[0], &mut bottom[2]);
+            std::mem::swap(&mut top[1], &mut bottom[3]);
+        }
+        sliced.middle_flipped = !sliced.middle_flipped;
+        sliced
+    }
+
+    fn corner_index(&self) -> usize {
+        rank(&self.corners) * 4 + self.offsets[0] * 2 + self.offsets[1]
+    }
+
+    fn edge_index(&self) -> usize {
+        (rank(&self.edges) * 2 + self.middle_flipped as usize) * 4
+            + self.offsets[0] * 2
+            + self.offsets[1]
+    }
+
+    /*
+    The turns before a slice that keep the cube's shape
+    */
+    fn turns(&self) -> impl Iterator<Item = (usize, usize)> {
+        let offsets = self.offsets;
+        turns(12).filter(move |&(top, bottom)| {
+            let top = (offsets[0] + top) % 3;
+            top < 2 && top == (offsets[1] + bottom) % 3
+        })
+    }
+}
+
+pub struct Tables {
+    shapes: Vec<(u16, u16)>,
+    // How many slices each shape and parity is from a cube the second phase can solve
+    shape_distances: HashMap<Node, u8>,
+    // How many slices each order of the corners or edges of a cube is from solved
+    corner_distances: Vec<u8>,
+    edge_distances: Vec<u8>,
+    // Those two are furthest below the real distance near solved, so the cubes there have theirs stored
+    near_distances: HashMap<(usize, usize), u8>,
+    goal: Cube,
+}
+
+impl Tables {
+    pub fn new() -> Tables {
+        let shape_distances = shape_distances();
+        let mut shapes: Vec<(u16, u16)> = shape_distances
+            .keys()
+            .filter(|(_, parity)| !parity)
+            .map(|&(shape, _)| shape)
+            .collect();
+        shapes.sort_unstable();
+        Tables {
+            shapes,
+            shape_distances,
+            corner_distances: cube_distances(N_CORNERS, Cube::corner_index),
+            edge_distances: cube_distances(N_EDGES, Cube::edge_index),
+            near_distances: near_distances(),
+            goal: Cube::new(&SOLVED).expect("The solved state should be a cube"),
+        }
+    }
+
+    /*
+    The turns from the state to solved, each but the last followed by a slice
+    */
+    fn solve(&self, state: Square1, rng: &mut impl Rng) -> Vec<(usize, usize)> {
+        let mut node_limit = NODE_LIMIT;
+        loop {
+            let (mut moves, cube) = self.first_phase(state, rng);
+            let mut nodes_left = node_limit;
+            let mut depth = 0;
+            while nodes_left > 0 {
+                if self.second_phase(cube, depth, &mut moves, &mut nodes_left) {
+                    return moves;
+                }
+                depth += 1;
+            }
+            // Some cubes take far longer to solve than others, so try getting to another, allowing longer in case every way leads to a slow one
+            node_limit *= 2;
+        }
+    }
+
+    /*
+    One of the shortest ways to the shape of a cube with the parity the second phase can solve
+    */
+    fn first_phase(&self, mut state: Square1, rng: &mut impl Rng) -> (Vec<(usize, usize)>, Cube) {
+        let mut moves = Vec::new();
+        loop {
+            let distance = self.shape_distances[&state.node()];
+            if distance == 0 {
+                let cube = Cube::new(&state).expect("The first phase should end in a cube");
+                return (moves, cube);
+            }
+            let closer: Vec<((usize, usize), Square1)> = turns(12)
+                .filter_map(|(top, bottom)| Some(((top, bottom), state.turn(top, bottom).slice()?)))
+                .filter(|(_, next)| self.shape_distances[&next.node()] == distance - 1)
+                .collect();
+            let &(turn, next) = closer
+                .choose(rng)
+                .expect("A slice should bring every shape closer to a cube");
+            moves.push(turn);
+            state = next;
+        }
+    }
+
+    /*
+    Iterative deepening over the cubes, solving it in exactly this many slices unless the nodes run out first
+    */
+    fn second_phase(
+        &self,
+        cube: Cube,
+        depth: u8,
+        moves: &mut Vec<(usize, usize)>,
+        nodes_left: &mut u32,
+    ) -> bool {
+        if *nodes_left == 0 {
+            return false;
+        }
+        *nodes_left -= 1;
+        let estimate = match self
+            .near_distances
+            .get(&(cube.corner_index(), cube.edge_index()))
+        {
+            Some(&distance) => distance,
+            None => (NEAR + 1)
+                .max(self.corner_distances[cube.corner_index()])
+                .max(self.edge_distances[cube.edge_index()]),
+        };
+        if estimate > depth {
+            return false;
+        }
+        if depth == 0 {
+            let Some(turn) = turns(12).find(|&(top, bottom)| cube.turn(top, bottom) == self.goal)
+            else {
+                return false;
+            };
+            moves.push(turn);
+            return true;
+        }
+        for (top, bottom) in cube.turns() {
+            // Slicing straight back would undo the last slice
+            if (top, bottom) == (0, 0) && !moves.is_empty() {
+                continue;
+            }
+            moves.push((top, bottom));
+            if self.second_phase(cube.turn(top, bottom).slice(), depth - 1, moves, nodes_left) {
+                return true;
+            }
+            moves.pop();
+        }
+        false
+    }
+}
+
+pub fn square_1(tables: &Tables) -> String {
+    let mut rng = rand::thread_rng();
+    let shape = *tables.shapes.choose(&mut rng).unwrap_or(&SOLVED.node().0);
+    let mut pieces: Vec<u8> = (0..16).collect();
+    pieces[..8].shuffle(&mut rng);
+    pieces[8..].shuffle(&mut rng);
+    let state = Square1::with_shape(shape);
+    let state = Square1 {
+        top: state.top.map(|piece| pieces[piece as usize]),
+        bottom: state.bottom.map(|piece| pieces[piece as usize]),
+        middle_flipped: rng.gen(),
+    };
+
+    // Undoing the solution scrambles a solved puzzle into the state
+    let moves = tables.solve(state, &mut rng);
+    let last = moves.len() - 1;
+    moves
+        .iter()
+        .rev()
+        .enumerate()
+        .filter_map(|(i, &(top, bottom))| {
+            let turn = match (top, bottom) {
+                (0, 0) => String::new(),
+                _ => format!("({},{})", notation(12 - top), notation(12 - bottom)),
+            };
+            match i < last {
+                true => Some(format!("{turn}/")),
+                false => (!turn.is_empty()).then_some(turn),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/*
+Every shape and parity the slice can turn from with its distance, found by searching back from the cubes with the parity of the solved state
+*/
+fn shape_distances() -> HashMap<Node, u8> {
+    let start = SOLVED.node();
+    let mut previous: HashMap<Node, Vec<Node>> = HashMap::from([(start, Vec::new())]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        let state = Square1::with_shape(node.0);
+        for (top, bottom) in turns(12) {
+            let Some(next) = state.turn(top, bottom).slice() else {
+                continue;
+            };
+            // The state was numbered with even parity, so the slice changed it by the parity of the result
+            let next = (next.node().0, next.parity() != node.1);
+            if !previous.contains_key(&next) {
+                queue.push_back(next);
+            }
+            previous.entry(next).or_default().push(node);
+        }
+    }
+
+    let cube_parity =
+        |((top, bottom), parity): Node| Some(parity ^ (offset(top)? == 1) ^ (offset(bottom)? == 1));
+    let goal_parity = cube_parity(start);
+    let mut distances: HashMap<Node, u8> = previous
+        .keys()
+        .filter(|&&node| cube_parity(node).is_some() && cube_parity(node) == goal_parity)
+        .map(|&node| (node, 0))
+        .collect();
+    let mut queue: VecDeque<Node> = distances.keys().copied().collect();
+    while let Some(node) = queue.pop_front() {
+        let distance = distances[&node] + 1;
+        for &before in &previous[&node] {
+            distances.entry(before).or_insert_with(|| {
+                queue.push_back(before);
+                distance
+            });
+        }
+    }
+    distances
+}
+
+/*
+How many slices each order of the corners or edges is from solved, found by searching back from every turn of the solved state
+*/
+fn cube_distances(size: usize, index: fn(&Cube) -> usize) -> Vec<u8> {
+    let solved = Cube::new(&SOLVED).expect("The solved state should be a cube");
+    let mut distances = vec![UNVISITED; size];
+    let mut queue = VecDeque::new();
+    for (top, bottom) in turns(12) {
+        let cube = solved.turn(top, bottom);
+        if cube.offsets.iter().all(|&offset| offset < 2) && distances[index(&cube)] == UNVISITED {
+            distances[index(&cube)] = 0;
+            queue.push_back(cube);
+        }
+    }
+    while let Some(cube) = queue.pop_front() {
+        // Only cubes with both layers turned the same can have just been sliced
+        if cube.offsets[0] != cube.offsets[1] {
+            continue;
+        }
+        let distance = distances[index(&cube)] + 1;
+        let sliced = cube.slice();
+        for (top, bottom) in turns(12) {
+            let next = sliced.turn(top, bottom);
+            if next.offsets.iter().all(|&offset| offset < 2) && distances[index(&next)] == UNVISITED
+            {
+                distances[index(&next)] = distance;
+                queue.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+/*
+Like the other cube distances but for whole cubes, so only for the few near solved
+*/
+fn near_distances() -> HashMap<(usize, usize), u8> {
+    let solved = Cube::new(&SOLVED).expect("The solved state should be a cube");
+    let index = |cube: &Cube| (cube.corner_index(), cube.edge_index());
+    let mut distances = HashMap::new();
+    let mut queue = VecDeque::new();
+    for (top, bottom) in turns(12) {
+        let cube = solved.turn(top, bottom);
+        if cube.offsets.iter().all(|&offset| offset < 2) && !distances.contains_key(&index(&cube)) {
+            distances.insert(index(&cube), 0);
+            queue.push_back(cube);
+        }
+    }
+    while let Some(cube) = queue.pop_front() {
+        let distance = distances[&index(&cube)] + 1;
+        if cube.offsets[0] != cube.offsets[1] || distance > NEAR {
+            continue;
+        }
+        let sliced = cube.slice();
+        for (top, bottom) in turns(12) {
+            let next = sliced.turn(top, bottom);
+            if next.offsets.iter().all(|&offset| offset < 2)
+                && !distances.contains_key(&index(&next))
+            {
+                distances.insert(index(&next), distance);
+                queue.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+fn turns(amounts: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..amounts).flat_map(move |top| (0..amounts).map(move |bottom| (top, bottom)))
+}
+
+/*
+No piece can be in the way of the slice on either side
+*/
+fn can_slice(layer: &[u8; 12]) -> bool {
+    layer[11] != layer[0] && layer[5] != layer[6]
+}
+
+/*
+The slots that start a piece
+*/
+fn shape(layer: &[u8; 12]) -> u16 {
+    (0..12)
+        .filter(|&slot| layer[slot] != layer[(slot + 11) % 12])
+        .fold(0, |shape, slot| shape | 1 << slot)
+}
+
+/*
+How many slots a square layer is turned past having a corner at slot 0, None for other shapes
+*/
+fn offset(shape: u16) -> Option<usize> {
+    (0..3).find(|&offset| (SQUARE << offset | SQUARE >> (12 - offset)) & 0xfff == shape)
+}
+
+// The position of each piece among the others, as a number below 8!
+fn rank(pieces: &[[u8; 4]; 2]) -> usize {
+    let pieces: [u8; 8] = std::array::from_fn(|i| pieces[i / 4][i % 4]);
+    (0..8).fold(0, |rank, i| {
+        rank * (8 - i)
+            + pieces[i + 1..]
+                .iter()
+                .filter(|&&later| later < pieces[i])
+                .count()
+    })
+}
+
+// A turn of a layer in slots, from -5 to 6
+fn notation(amount: usize) -> i32 {
+    match amount % 12 {
+        amount if amount > 6 => amount as i32 - 12,
+        amount => amount as i32,
+    }
+}
@@ -0,0 +1,226 @@
+/*
+Puzzles small enough to be solved optimally by searching from both the scrambled and the solved state at once, used for random-state scrambles of the 2x2, Pyraminx and Skewb
+*/
+
+use rand::Rng;
+use std::collections::HashMap;
+
+// A point in space, with x to the right, y up and z towards the front
+pub type Point = [f64; 3];
+
+/*
+The colour of every sticker, and every move as where each sticker ends up
+*/
+pub struct StickerPuzzle {
+    solved: Vec<u8>,
+    moves: Vec<Vec<usize>>,
+    names: Vec<String>,
+    // The index of the move that undoes each move
+    inverses: Vec<usize>,
+}
+
+impl StickerPuzzle {
+    /*
+    Each turn is given as its name, its permutation and how many times it can be applied before the puzzle is back where it started
+    */
+    pub fn new(solved: Vec<u8>, turns: Vec<(&str, Vec<usize>, usize)>) -> StickerPuzzle {
+        let mut puzzle = StickerPuzzle {
+            solved,
+            moves: Vec::new(),
+            names: Vec::new(),
+            inverses: Vec::new(),
+        };
+        for (name, permutation, order) in turns {
+            let first = puzzle.moves.len();
+            let mut power = permutation.clone();
+            for amount in 1..order {
+                let suffix = match (amount, order) {
+                    (1, _) => String::new(),
+                    (amount, order) if amount == order - 1 => "'".to_string(),
+                    (amount, _) => amount.to_string(),
+                };
+                puzzle.names.push(format!("{name}{suffix}"));
+                puzzle.inverses.push(first + order - 1 - amount);
+                puzzle.moves.push(power.clone());
+                power = power.iter().map(|&index| permutation[index]).collect();
+            }
+        }
+        puzzle
+    }
+
+    /*
+    Works out the turns from where the stickers are, turning everything further along the axis than the cut clockwise as seen from that side
+    */
+    pub fn from_geometry(
+        stickers: &[(Point, u8)],
+        turns: &[(&str, Point, f64)],
+        order: usize,
+    ) -> StickerPuzzle {
+        let angle = -2.0 * std::f64::consts::PI / order as f64;
+        let turns = turns
+            .iter()
+            .map(|&(name, axis, cut)| {
+                let length = dot(axis, axis).sqrt();
+                let axis = axis.map(|value| value / length);
+                let permutation = stickers
+                    .iter()
+                    .enumerate()
+                    .map(|(index, &(position, _))| {
+                        if dot(position, axis) <= cut {
+                            return index;
+                        }
+                        nearest(stickers, rotate(position, axis, angle))
+                    })
+                    .collect();
+                (name, permutation, order)
+            })
+            .collect();
+        let solved = stickers.iter().map(|&(_, colour)| colour).collect();
+        StickerPuzzle::new(solved, turns)
+    }
+
+    /*
+    A scramble to a random state at least `min_length` moves from solved
+    */
+    pub fn random_state_scramble(&self, min_length: usize) -> String {
+        let mut rng = rand::thread_rng();
+        loop {
+            // A long enough random walk reaches every state with practically the same probability
+            let mut state = self.solved.clone();
+            for _ in 0..1000 {
+                state = self.apply(&state, rng.gen_range(0..self.moves.len()));
+            }
+            let solution = self.solve(&state);
+            if solution.len() < min_length {
+                continue;
+            }
+            // Undoing the solution scrambles a solved puzzle into this state
+            return solution
+                .iter()
+                .rev()
+                .map(|&m| self.names[self.inverses[m]].as_str())
+                .collect::<Vec<&str>>()
+                .join(" ");
+        }
+    }
+
+    fn apply(&self, state: &[u8], m: usize) -> Vec<u8> {
+        let mut next = state.to_vec();
+        for (index, &destination) in self.moves[m].iter().enumerate() {
+            next[destination] = state[index];
+        }
+        next
+    }
+
+    /*
+    Breadth-first search from both ends, always growing the side with the smaller frontier
+    */
+    fn solve(&self, state: &[u8]) -> Vec<usize> {
+        if state == self.solved.as_slice() {
+            return Vec::new();
+        }
+        let mut forward = Side::new(state.to_vec());
+        let mut backward = Side::new(self.solved.clone());
+        loop {
+            let forward_is_smaller = forward.frontier.len() <= backward.frontier.len();
+            let (side, other) = if forward_is_smaller {
+                (&mut forward, &backward)
+            } else {
+                (&mut backward, &forward)
+            };
+            let Some(meeting) = side.expand(self, other) else {
+                continue;
+            };
+            let mut solution = forward.path(&meeting);
+            let mut rest = backward.path(&meeting);
+            rest.reverse();
+            solution.extend(rest.into_iter().map(|m| self.inverses[m]));
+            return solution;
+        }
+    }
+}
+
+/*
+One direction of the search, with the move that first reached each state
+*/
+struct Side {
+    reached: HashMap<Vec<u8>, Option<(Vec<u8>, usize)>>,
+    frontier: Vec<Vec<u8>>,
+}
+
+impl Side {
+    fn new(start: Vec<u8>) -> Side {
+        Side {
+            reached: HashMap::from([(start.clone(), None)]),
+            frontier: vec![start],
+        }
+    }
+
+    /*
+    Searches one move further, returning a state the other side has already reached if there is one
+    */
+    fn expand(&mut self, puzzle: &StickerPuzzle, other: &Side) -> Option<Vec<u8>> {
+        let mut frontier = Vec::new();
+        let mut meeting = None;
+        for state in std::mem::take(&mut self.frontier) {
+            for m in 0..puzzle.moves.len() {
+                let next = puzzle.apply(&state, m);
+                if self.reached.contains_key(&next) {
+                    continue;
+                }
+                self.reached.insert(next.clone(), Some((state.clone(), m)));
+                if other.reached.contains_key(&next) {
+                    meeting = Some(next.clone());
+                }
+                frontier.push(next);
+            }
+            if meeting.is_some() {
+                break;
+            }
+        }
+        self.frontier = frontier;
+        meeting
+    }
+
+    /*
+    The moves from the start of this side to the state
+    */
+    fn path(&self, state: &[u8]) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut state = state.to_vec();
+        while let Some((previous, m)) = &self.reached[&state] {
+            path.push(*m);
+            state = previous.clone();
+        }
+        path.reverse();
+        path
+    }
+}
+
+fn dot(a: Point, b: Point) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/*
+Rodrigues' rotation formula, turning anticlockwise around the unit axis for a positive angle
+*/
+fn rotate(v: Point, axis: Point, angle: f64) -> Point {
+    let (sin, cos) = angle.sin_cos();
+    let cross = [
+        axis[1] * v[2] - axis[2] * v[1],
+        axis[2] * v[0] - axis[0] * v[2],
+        axis[0] * v[1] - axis[1] * v[0],
+    ];
+    let along = dot(axis, v) * (1.0 - cos);
+    [0, 1, 2].map(|i| v[i] * cos + cross[i] * sin + axis[i] * along)
+}
+
+fn nearest(stickers: &[(Point, u8)], point: Point) -> usize {
+    let distance = |position: Point| {
+        let difference = [0, 1, 2].map(|i| position[i] - point[i]);
+        dot(difference, difference)
+    };
+    (0..stickers.len())
+        .min_by(|&a, &b| distance(stickers[a].0).total_cmp(&distance(stickers[b].0)))
+        .unwrap()
+}
@@ -0,0 +1,127 @@
+/*
+Named sessions, each keeping the solves of one puzzle apart from the others
+*/
+
+use crate::{puzzle::Puzzle, solve::Solve};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, time::Duration};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub name: String,
+    pub puzzle: Puzzle,
+    pub solves: Vec<Solve>,
+}
+
+impl Session {
+    pub fn new(name: String, puzzle: Puzzle) -> Session {
+        Session {
+            name,
+            puzzle,
+            solves: Vec::new(),
+        }
+    }
+}
+
+/*
+Every session and which one is open
+*/
+#[derive(Serialize, Deserialize)]
+pub struct Sessions {
+    pub active: usize,
+    pub sessions: Vec<Session>,
+}
+
+impl Sessions {
+    /*
+    Splits solves saved before there were sessions into one session per puzzle, opening the one with the last solve
+    */
+    pub fn from_solves(solves: Vec<Solve>) -> Sessions {
+        let mut sessions = Sessions {
+            active: 0,
+            sessions: Vec::new(),
+        };
+        let last_puzzle = solves.last().map(|solve| solve.puzzle);
+        for puzzle in Puzzle::ALL {
+            let solves: Vec<Solve> = solves
+                .iter()
+                .filter(|solve| solve.puzzle == puzzle)
+                .cloned()
+                .collect();
+            if solves.is_empty() {
+                continue;
+            }
+            if Some(puzzle) == last_puzzle {
+                sessions.active = sessions.sessions.len();
+            }
+            sessions.sessions.push(Session {
+                name: puzzle.name().to_string(),
+                puzzle,
+                solves,
+            });
+        }
+        if sessions.sessions.is_empty() {
+            return Sessions::default();
+        }
+        sessions
+    }
+
+    /*
+    Adds a session at the index, keeping the same session open
+    */
+    pub fn insert(&mut self, index: usize, session: Session) {
+        self.sessions.insert(index, session);
+        if index <= self.active {
+            self.active += 1;
+        }
+    }
+
+    /*
+    Removes the session at the index, opening the one that takes its place if it was open
+    */
+    pub fn remove(&mut self, index: usize) -> Session {
+        let session = self.sessions.remove(index);
+        if index < self.active || self.active == self.sessions.len() {
+            self.active -= 1;
+        }
+        session
+    }
+}
+
+impl Default for Sessions {
+    fn default() -> Sessions {
+        let puzzle = Puzzle::ThreeByThree;
+        Sessions {
+            active: 0,
+            sessions: vec![Session::new(puzzle.name().to_string(), puzzle)],
+        }
+    }
+}
+
+/*
+Adds the solves that are not already in the list, keeping it in the order they were done, and returns the ones that were added
+*/
+pub fn add_solves(solves: &mut Vec<Solve>, new: Vec<Solve>) -> Vec<Solve> {
+    let mut added = Vec::new();
+    let mut seen: HashSet<(u64, Duration, String)> = solves
+        .iter()
+        .map(|solve| (solve.timestamp, solve.time, solve.scramble.clone()))
+        .collect();
+    for solve in new {
+        if seen.insert((solve.timestamp, solve.time, solve.scramble.clone())) {
+            added.push(solve.clone());
+            solves.push(solve);
+        }
+    }
+    solves.sort_by_key(|solve| solve.timestamp);
+    added
+}
+
+/*
+Whether they are the same attempt, whatever its penalty or comment
+*/
+pub fn is_same(solve: &Solve, other: &Solve) -> bool {
+    solve.timestamp == other.timestamp
+        && solve.time == other.time
+        && solve.scramble == other.scramble
+}
@@ -0,0 +1,115 @@
+/*
+A single attempt and its result
+*/
+
+use crate::puzzle::Puzzle;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Penalty {
+    None,
+    PlusTwo,
+    Dnf,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Solve {
+    #[serde(rename = "time_ms", with = "milliseconds")]
+    pub time: Duration,
+    pub penalty: Penalty,
+    // Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub scramble: String,
+    pub puzzle: Puzzle,
+    // Solves saved before there were comments have none
+    #[serde(default)]
+    pub comment: String,
+    // When each phase but the last ended, from the start of the solve, empty unless it was split
+    #[serde(
+        rename = "splits_ms",
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "milliseconds_list"
+    )]
+    pub splits: Vec<Duration>,
+}
+
+impl Solve {
+    pub fn new(time: Duration, penalty: Penalty, scramble: String, puzzle: Puzzle) -> Solve {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_millis() as u64);
+        Solve {
+            time,
+            penalty,
+            timestamp,
+            scramble,
+            puzzle,
+            comment: String::new(),
+            splits: Vec::new(),
+        }
+    }
+
+    /*
+    The time counted towards statistics, None for a DNF
+    */
+    pub fn result(&self) -> Option<Duration> {
+        match self.penalty {
+            Penalty::None => Some(self.time),
+            Penalty::PlusTwo => Some(self.time + Duration::from_secs(2)),
+            Penalty::Dnf => None,
+        }
+    }
+
+    /*
+    How long each phase took, the last one ending when the timer stopped
+    */
+    pub fn phase_times(&self) -> Vec<Duration> {
+        if self.splits.is_empty() {
+            return Vec::new();
+        }
+        let mut start = Duration::ZERO;
+        let mut times = Vec::new();
+        for &end in self.splits.iter().chain([&self.time]) {
+            times.push(end.saturating_sub(start));
+            start = end;
+        }
+        times
+    }
+}
+
+/*
+Times are stored as whole milliseconds so the file stays readable
+*/
+mod milliseconds {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(time: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(time.as_millis() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+}
+
+mod milliseconds_list {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(times: &[Duration], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(times.iter().map(|time| time.as_millis() as u64))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Duration>, D::Error> {
+        let milliseconds = Vec::<u64>::deserialize(deserializer)?;
+        Ok(milliseconds
+            .into_iter()
+            .map(Duration::from_millis)
+            .collect())
+    }
+}
@@ -0,0 +1,123 @@
+/*
+Means and averages of the most recent solves, following the WCA rules
+*/
+
+use crate::solve::Solve;
+use std::time::Duration;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Average {
+    Time(Duration),
+    Dnf,
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    // Every solve counts, so a single DNF makes it a DNF
+    Mean,
+    // The best and worst 5% are trimmed
+    Average,
+}
+
+// Phase times are the mean of up to this many of the latest solves split into every phase
+const PHASE_SOLVES: usize = 12;
+
+const STATISTICS: [(&str, Kind, usize); 6] = [
+    ("mo3", Kind::Mean, 3),
+    ("ao5", Kind::Average, 5),
+    ("ao12", Kind::Average, 12),
+    ("ao50", Kind::Average, 50),
+    ("ao100", Kind::Average, 100),
+    ("ao1000", Kind::Average, 1000),
+];
+
+/*
+The statistics of a list of solves, kept up to date by the app whenever the solves change
+*/
+pub struct Statistics {
+    // The name and value of each statistic ending at the latest solve, None if there are not enough solves
+    pub current: Vec<(&'static str, Option<Average>)>,
+    // The ao5 and ao12 ending at each solve
+    pub rolling: Vec<(Option<Average>, Option<Average>)>,
+    // The mean time of each phase, empty without any split solves
+    pub phases: Vec<Duration>,
+    // How many solves the phase times are from
+    pub phase_solves: usize,
+}
+
+impl Statistics {
+    /*
+    Solves count towards the phase times if they were finished and split into the same number of phases
+    */
+    pub fn new(solves: &[Solve], phases: usize) -> Statistics {
+        let current = STATISTICS
+            .iter()
+            .map(|&(name, kind, count)| (name, last(solves, kind, count)))
+            .collect();
+        let rolling = (1..=solves.len())
+            .map(|end| {
+                (
+                    last(&solves[..end], Kind::Average, 5),
+                    last(&solves[..end], Kind::Average, 12),
+                )
+            })
+            .collect();
+        let split: Vec<Vec<Duration>> = solves
+            .iter()
+            .rev()
+            .filter(|solve| solve.result().is_some() && solve.splits.len() + 1 == phases)
+            .take(PHASE_SOLVES)
+            .map(Solve::phase_times)
+            .collect();
+        let phase_solves = split.len();
+        let phases = if phase_solves > 0 {
+            (0..phases)
+                .map(|phase| {
+                    let total: Duration = split.iter().map(|times| times[phase]).sum();
+                    total / phase_solves as u32
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        Statistics {
+            current,
+            rolling,
+            phases,
+            phase_solves,
+        }
+    }
+}
+
+fn last(solves: &[Solve], kind: Kind, count: usize) -> Option<Average> {
+    let solves = solves.get(solves.len().checked_sub(count)?..)?;
+    Some(match kind {
+        Kind::Mean => mean(solves),
+        Kind::Average => average(solves),
+    })
+}
+
+pub fn mean(solves: &[Solve]) -> Average {
+    let results: Option<Vec<Duration>> = solves.iter().map(Solve::result).collect();
+    match results {
+        Some(results) => Average::Time(results.iter().sum::<Duration>() / results.len() as u32),
+        None => Average::Dnf,
+    }
+}
+
+/*
+The mean after removing the best and worst 5% (rounded up) of the solves, DNFs count as the worst
+*/
+pub fn average(solves: &[Solve]) -> Average {
+    let trim = (solves.len() * 5).div_ceil(100);
+    let mut results: Vec<Option<Duration>> = solves.iter().map(Solve::result).collect();
+    // DNFs sort after every time
+    results.sort_by_key(|result| (result.is_none(), *result));
+    let counting = &results[trim..results.len() - trim];
+    let counting: Option<Vec<Duration>> = counting.iter().copied().collect();
+    match counting {
+        Some(counting) => Average::Time(counting.iter().sum::<Duration>() / counting.len() as u32),
+        // More DNFs than can be trimmed
+        None => Average::Dnf,
+    }
+}
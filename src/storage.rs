@@ -0,0 +1,118 @@
+/*
+Saving and loading sessions and their history under the XDG data directory, and the locations of other files
+*/
+
+use crate::{history::History, session::Sessions, solve::Solve};
+use serde::de::DeserializeOwned;
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+const SESSIONS_FILE: &str = "sessions.json";
+const HISTORY_FILE: &str = "history.json";
+// Where solves were saved before there were sessions
+const SOLVES_FILE: &str = "solves.json";
+
+fn data_dir() -> io::Result<PathBuf> {
+    let data_dir = dirs::data_dir().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, "Could not find the data directory")
+    })?;
+    Ok(data_dir.join("terminal_cubing"))
+}
+
+pub fn cache_dir() -> io::Result<PathBuf> {
+    let cache_dir = dirs::cache_dir().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "Could not find the cache directory",
+        )
+    })?;
+    Ok(cache_dir.join("terminal_cubing"))
+}
+
+pub fn config_dir() -> io::Result<PathBuf> {
+    let config_dir = dirs::config_dir().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "Could not find the config directory",
+        )
+    })?;
+    Ok(config_dir.join("terminal_cubing"))
+}
+
+pub fn load_sessions() -> io::Result<Sessions> {
+    let dir = data_dir()?;
+    let path = dir.join(SESSIONS_FILE);
+    if let Some(sessions) = read_json::<Sessions>(&path)? {
+        // The file could have been edited by hand, and the open session is looked up straight away
+        if sessions.active >= sessions.sessions.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{}: the open session {} is not one of the {} sessions",
+                    path.display(),
+                    sessions.active,
+                    sessions.sessions.len()
+                ),
+            ));
+        }
+        return Ok(sessions);
+    }
+    let solves: Option<Vec<Solve>> = read_json(&dir.join(SOLVES_FILE))?;
+    Ok(solves.map_or_else(Sessions::default, Sessions::from_solves))
+}
+
+pub fn save_sessions(sessions: &Sessions) -> io::Result<()> {
+    let dir = data_dir()?;
+    fs::create_dir_all(&dir)?;
+    let contents = serde_json::to_vec_pretty(sessions)?;
+    write_atomic(&dir.join(SESSIONS_FILE), &contents)
+}
+
+pub fn load_history() -> io::Result<History> {
+    let history = read_json(&data_dir()?.join(HISTORY_FILE))?;
+    Ok(history.unwrap_or_default())
+}
+
+pub fn save_history(history: &History) -> io::Result<()> {
+    let dir = data_dir()?;
+    fs::create_dir_all(&dir)?;
+    let contents = serde_json::to_vec(history)?;
+    write_atomic(&dir.join(HISTORY_FILE), &contents)
+}
+
+/*
+None if the file has not been saved yet
+*/
+fn read_json<T: DeserializeOwned>(path: &Path) -> io::Result<Option<T>> {
+    let contents = match fs::read(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    serde_json::from_slice(&contents).map(Some).map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), err),
+        )
+    })
+}
+
+/*
+Write to a temporary file and rename it over the old one, so being killed mid-write leaves either the old or the new file intact
+*/
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let temp_path = path.with_extension("tmp");
+    let mut file = File::create(&temp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    fs::rename(&temp_path, path)?;
+    // Make sure the rename itself reaches the disk
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
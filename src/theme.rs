@@ -0,0 +1,226 @@
+/*
+The colours of the interface, from one of the built-in themes or one defined in the config
+
+A theme of the user's own is a table of colours under `[themes.<name>]`, starting from the theme named by its `base` or the dark theme. Colours are names like `light_green` or `#rrggbb`. The stickers of the scramble preview keep the colours of the cube whatever the theme.
+*/
+
+use std::collections::BTreeMap;
+use tui::style::{Color, Modifier, Style};
+
+const BUILT_IN: [&str; 4] = ["dark", "light", "high_contrast", "solarized"];
+
+// The user's themes by name, each a colour by the part it is for
+pub type Themes = BTreeMap<String, BTreeMap<String, String>>;
+
+#[derive(Clone, Copy)]
+pub struct Theme {
+    // Everything not given its own colours
+    foreground: Color,
+    background: Color,
+    // The rows of the list of times
+    list_foreground: Color,
+    list_background: Color,
+    // The selected row of a list
+    highlight_foreground: Color,
+    highlight_background: Color,
+    // The time while the timer key is held, too early and then long enough to start
+    holding: Color,
+    ready: Color,
+    // The inspection call outs
+    warning: Color,
+    stats: Color,
+    // The keys in the help along the bottom
+    keys: Color,
+}
+
+impl Theme {
+    fn built_in(name: &str) -> Option<Theme> {
+        let theme = match name {
+            "dark" => Theme {
+                foreground: Color::White,
+                background: Color::Black,
+                list_foreground: Color::Black,
+                list_background: Color::White,
+                highlight_foreground: Color::Black,
+                highlight_background: Color::LightGreen,
+                holding: Color::Red,
+                ready: Color::Green,
+                warning: Color::Yellow,
+                stats: Color::Red,
+                keys: Color::Blue,
+            },
+            "light" => Theme {
+                foreground: Color::Black,
+                background: Color::White,
+                list_foreground: Color::Black,
+                list_background: Color::Gray,
+                highlight_foreground: Color::White,
+                highlight_background: Color::Blue,
+                holding: Color::Red,
+                ready: Color::Green,
+                warning: Color::Magenta,
+                stats: Color::Red,
+                keys: Color::Blue,
+            },
+            "high_contrast" => Theme {
+                foreground: Color::White,
+                background: Color::Black,
+                list_foreground: Color::White,
+                list_background: Color::Black,
+                highlight_foreground: Color::Black,
+                highlight_background: Color::Yellow,
+                holding: Color::LightRed,
+                ready: Color::LightGreen,
+                warning: Color::LightYellow,
+                stats: Color::LightCyan,
+                keys: Color::LightYellow,
+            },
+            "solarized" => Theme {
+                foreground: Color::Rgb(0x83, 0x94, 0x96),
+                background: Color::Rgb(0x00, 0x2b, 0x36),
+                list_foreground: Color::Rgb(0x93, 0xa1, 0xa1),
+                list_background: Color::Rgb(0x07, 0x36, 0x42),
+                highlight_foreground: Color::Rgb(0x00, 0x2b, 0x36),
+                highlight_background: Color::Rgb(0x2a, 0xa1, 0x98),
+                holding: Color::Rgb(0xdc, 0x32, 0x2f),
+                ready: Color::Rgb(0x85, 0x99, 0x00),
+                warning: Color::Rgb(0xb5, 0x89, 0x00),
+                stats: Color::Rgb(0xcb, 0x4b, 0x16),
+                keys: Color::Rgb(0x26, 0x8b, 0xd2),
+            },
+            _ => return None,
+        };
+        Some(theme)
+    }
+
+    /*
+    The theme with the name, looking at the user's themes before the built-in ones
+    */
+    pub fn named(name: &str, themes: &Themes) -> Result<Theme, String> {
+        let Some(colours) = themes.get(name) else {
+            return Theme::built_in(name).ok_or_else(|| format!("There is no theme \"{name}\""));
+        };
+        let base = colours.get("base").map_or("dark", String::as_str);
+        let mut theme = Theme::built_in(base).ok_or_else(|| {
+            format!("The base of theme \"{name}\" should be one of the built-in themes")
+        })?;
+        for (part, colour) in colours {
+            if part == "base" {
+                continue;
+            }
+            let slot = theme
+                .slot(part)
+                .ok_or_else(|| format!("Theme \"{name}\" has no part called {part}"))?;
+            *slot = parse_colour(colour)
+                .ok_or_else(|| format!("Theme \"{name}\" has an unknown colour \"{colour}\""))?;
+        }
+        Ok(theme)
+    }
+
+    fn slot(&mut self, part: &str) -> Option<&mut Color> {
+        let slot = match part {
+            "foreground" => &mut self.foreground,
+            "background" => &mut self.background,
+            "list_foreground" => &mut self.list_foreground,
+            "list_background" => &mut self.list_background,
+            "highlight_foreground" => &mut self.highlight_foreground,
+            "highlight_background" => &mut self.highlight_background,
+            "holding" => &mut self.holding,
+            "ready" => &mut self.ready,
+            "warning" => &mut self.warning,
+            "stats" => &mut self.stats,
+            "keys" => &mut self.keys,
+            _ => return None,
+        };
+        Some(slot)
+    }
+
+    pub fn text(&self) -> Style {
+        Style::default().fg(self.foreground).bg(self.background)
+    }
+
+    pub fn list(&self) -> Style {
+        Style::default()
+            .fg(self.list_foreground)
+            .bg(self.list_background)
+    }
+
+    pub fn highlight(&self) -> Style {
+        Style::default()
+            .fg(self.highlight_foreground)
+            .bg(self.highlight_background)
+            .add_modifier(Modifier::BOLD)
+    }
+
+    pub fn holding(&self) -> Style {
+        Style::default().fg(self.holding)
+    }
+
+    pub fn ready(&self) -> Style {
+        Style::default().fg(self.ready)
+    }
+
+    pub fn warning(&self) -> Style {
+        Style::default()
+            .fg(self.warning)
+            .add_modifier(Modifier::BOLD)
+    }
+
+    pub fn stats(&self) -> Style {
+        Style::default().fg(self.stats)
+    }
+
+    pub fn key(&self) -> Style {
+        Style::default().fg(self.keys).add_modifier(Modifier::BOLD)
+    }
+
+    pub fn scramble(&self) -> Style {
+        Style::default().add_modifier(Modifier::BOLD)
+    }
+}
+
+/*
+The names of every theme, the built-in ones first
+*/
+pub fn names(themes: &Themes) -> Vec<&str> {
+    let mut names = BUILT_IN.to_vec();
+    names.extend(
+        themes
+            .keys()
+            .map(String::as_str)
+            .filter(|name| !BUILT_IN.contains(name)),
+    );
+    names
+}
+
+fn parse_colour(colour: &str) -> Option<Color> {
+    if let Some(hex) = colour.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+        return Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+    }
+    let colour = match colour.to_lowercase().replace(' ', "_").as_str() {
+        // The terminal's own colour
+        "default" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "dark_gray" | "dark_grey" => Color::DarkGray,
+        "light_red" => Color::LightRed,
+        "light_green" => Color::LightGreen,
+        "light_yellow" => Color::LightYellow,
+        "light_blue" => Color::LightBlue,
+        "light_magenta" => Color::LightMagenta,
+        "light_cyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return None,
+    };
+    Some(colour)
+}
@@ -0,0 +1,249 @@
+/*
+The timer state machine
+
+All times are taken from `Instant` timestamps, so the result of a solve does not depend on how often the main loop ticks.
+*/
+
+use crate::solve::Penalty;
+use std::time::{Duration, Instant};
+
+/*
+Like WCA inspection, starting after the inspection time is a +2 and more than 2 seconds after it a DNF
+*/
+const INSPECTION_GRACE: Duration = Duration::from_secs(2);
+// The judge calls out the inspection when this much of it is left, 8 and 12 seconds in for the WCA's 15
+const INSPECTION_WARNINGS: [Duration; 2] = [Duration::from_secs(3), Duration::from_secs(7)];
+
+#[derive(PartialEq)]
+pub enum TimerStatus {
+    Countdown,
+    // The key is held but not for long enough to start
+    Holding,
+    // The key has been held long enough, releasing it starts the solve
+    Ready,
+    Countup,
+    Paused,
+}
+
+/*
+Tracks whether the timer key is held down
+*/
+pub struct TimerKey {
+    // Whether the terminal sends release events, otherwise releases are guessed from the repeats
+    reports_releases: bool,
+    /*
+    Without release events the terminal only sends repeating key presses while a key is held, so if no press arrives within this time we can assume it was released
+    */
+    pub release_threshold: Duration,
+    last_seen: Option<Instant>,
+}
+
+impl TimerKey {
+    pub fn new(reports_releases: bool, release_threshold: Duration) -> TimerKey {
+        TimerKey {
+            reports_releases,
+            release_threshold,
+            last_seen: None,
+        }
+    }
+
+    /*
+    Returns true if this is a new press rather than a repeat of a held key
+    */
+    pub fn press(&mut self, now: Instant) -> bool {
+        let is_new = self.last_seen.is_none();
+        self.last_seen = Some(now);
+        is_new
+    }
+
+    pub fn release(&mut self) {
+        self.last_seen = None;
+    }
+
+    /*
+    For terminals without release events, returns when the key is assumed to have been released
+    */
+    pub fn check_release(&mut self, now: Instant) -> Option<Instant> {
+        if self.reports_releases {
+            return None;
+        }
+        let last_seen = self.last_seen?;
+        if now.duration_since(last_seen) < self.release_threshold {
+            return None;
+        }
+        self.last_seen = None;
+        // The last repeat is the best guess of when the key was released
+        Some(last_seen)
+    }
+}
+
+pub struct Timer {
+    pub status: TimerStatus,
+    // How long the key has to be held before the timer is ready to start
+    pub hold_time: Duration,
+    pub inspection_enabled: bool,
+    pub inspection_time: Duration,
+    // How many phases a solve is split into, pressing the key ends each one until the last
+    pub phases: usize,
+    // When the current countup started
+    started_at: Instant,
+    inspection_started_at: Option<Instant>,
+    hold_started_at: Instant,
+    // The penalty of the current or last attempt
+    penalty: Penalty,
+    // The result shown while paused
+    last_time: Duration,
+    // When each phase of the current or last solve ended
+    splits: Vec<Duration>,
+}
+
+impl Timer {
+    pub fn new(
+        hold_time: Duration,
+        inspection_enabled: bool,
+        inspection_time: Duration,
+        phases: usize,
+    ) -> Timer {
+        let now = Instant::now();
+        Timer {
+            status: TimerStatus::Paused,
+            hold_time,
+            inspection_enabled,
+            inspection_time,
+            phases,
+            started_at: now,
+            inspection_started_at: None,
+            hold_started_at: now,
+            penalty: Penalty::None,
+            last_time: Duration::ZERO,
+            splits: Vec::new(),
+        }
+    }
+
+    pub fn key_down(&mut self, now: Instant) {
+        match self.status {
+            TimerStatus::Paused => {
+                self.penalty = Penalty::None;
+                self.splits.clear();
+                if self.inspection_enabled {
+                    self.inspection_started_at = Some(now);
+                    self.status = TimerStatus::Countdown;
+                } else {
+                    self.hold(now);
+                }
+            }
+            TimerStatus::Countdown => self.hold(now),
+            _ => {}
+        }
+    }
+
+    pub fn key_up(&mut self, now: Instant) {
+        match self.status {
+            // Check the release time rather than the status, as without release events the release is only known after the timer may have turned ready
+            TimerStatus::Holding | TimerStatus::Ready
+                if now.duration_since(self.hold_started_at) < self.hold_time =>
+            {
+                // Released too early, go back to where the hold started
+                self.status = match self.inspection_started_at {
+                    Some(_) => TimerStatus::Countdown,
+                    None => TimerStatus::Paused,
+                };
+            }
+            TimerStatus::Holding | TimerStatus::Ready => self.start(now),
+            _ => {}
+        }
+    }
+
+    /*
+    Returns the time and penalty of the attempt if it ended
+    */
+    pub fn update(&mut self, now: Instant) -> Option<(Duration, Penalty)> {
+        if self.status == TimerStatus::Holding
+            && now.duration_since(self.hold_started_at) >= self.hold_time
+        {
+            self.status = TimerStatus::Ready;
+        }
+
+        let inspection_started_at = self.inspection_started_at?;
+        if now.duration_since(inspection_started_at) <= self.inspection_time + INSPECTION_GRACE {
+            return None;
+        }
+        // The solve was not started in time
+        self.inspection_started_at = None;
+        self.penalty = Penalty::Dnf;
+        self.last_time = Duration::ZERO;
+        self.status = TimerStatus::Paused;
+        Some((self.last_time, self.penalty))
+    }
+
+    /*
+    Ends the current phase, false if it is the last one and the solve should stop instead
+    */
+    pub fn split(&mut self, now: Instant) -> bool {
+        if self.status != TimerStatus::Countup || self.splits.len() + 1 >= self.phases {
+            return false;
+        }
+        self.splits.push(now.duration_since(self.started_at));
+        true
+    }
+
+    pub fn splits(&self) -> &[Duration] {
+        &self.splits
+    }
+
+    pub fn stop(&mut self, now: Instant) -> Option<(Duration, Penalty)> {
+        if self.status != TimerStatus::Countup {
+            return None;
+        }
+        self.last_time = now.duration_since(self.started_at);
+        self.status = TimerStatus::Paused;
+        Some((self.last_time, self.penalty))
+    }
+
+    fn hold(&mut self, now: Instant) {
+        self.hold_started_at = now;
+        self.status = TimerStatus::Holding;
+    }
+
+    fn start(&mut self, now: Instant) {
+        if let Some(inspection_started_at) = self.inspection_started_at.take() {
+            if now.duration_since(inspection_started_at) > self.inspection_time {
+                self.penalty = Penalty::PlusTwo;
+            }
+        }
+        self.started_at = now;
+        self.status = TimerStatus::Countup;
+    }
+
+    /*
+    The inspection point that was last passed, if any
+    */
+    pub fn inspection_warning(&self, now: Instant) -> Option<u64> {
+        let elapsed = now.duration_since(self.inspection_started_at?);
+        INSPECTION_WARNINGS
+            .iter()
+            .filter_map(|&left| self.inspection_time.checked_sub(left))
+            .find(|&warning| !warning.is_zero() && elapsed >= warning)
+            .map(|warning| warning.as_secs())
+    }
+
+    pub fn penalty(&self) -> Penalty {
+        self.penalty
+    }
+
+    /*
+    The time to display in milliseconds, the remaining inspection before the solve and negative once it runs out
+    */
+    pub fn time_ms(&self, now: Instant) -> i64 {
+        match self.status {
+            TimerStatus::Countup => now.duration_since(self.started_at).as_millis() as i64,
+            TimerStatus::Paused => self.last_time.as_millis() as i64,
+            _ => self
+                .inspection_started_at
+                .map_or(0, |inspection_started_at| {
+                    self.inspection_time.as_millis() as i64
+                        - now.duration_since(inspection_started_at).as_millis() as i64
+                }),
+        }
+    }
+}
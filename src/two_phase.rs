@@ -0,0 +1,576 @@
+/*
+Kociemba's two-phase algorithm, used to generate random-state 3x3 scrambles
+
+Phase 1 brings the cube into the subgroup <U, D, R2, L2, F2, B2>, where every piece is oriented and the middle slice edges are in the middle slice.
+Phase 2 then solves the cube using only those moves.
+Both phases are iterative deepening searches over coordinates of the cube, using move tables to turn them and pruning tables as the heuristic.
+*/
+
+use crate::{
+    cube::{CubieCube, FaceletCube, FACE_TURNS},
+    storage,
+};
+use rand::Rng;
+use std::{collections::VecDeque, fs, io};
+
+const N_MOVES: usize = 18;
+const N_TWIST: usize = 2187; // 3^7 corner orientations
+const N_FLIP: usize = 2048; // 2^11 edge orientations
+const N_SLICE: usize = 495; // 12 choose 4 positions of the middle slice edges
+const N_CORNER_PERM: usize = 40320; // 8! corner permutations
+const N_EDGE_PERM: usize = 40320; // 8! permutations of the U and D edges in phase 2
+const N_SLICE_PERM: usize = 24; // 4! permutations of the middle slice edges in phase 2
+
+// Generated scrambles are at most this long
+const MAX_LENGTH: usize = 21;
+// A move is its face (in the order U R F D L B) times 3 plus its number of quarter turns minus 1
+const PHASE_2_MOVES: [usize; 10] = [0, 1, 2, 4, 7, 9, 10, 11, 13, 16];
+const FACE_NAMES: [char; 6] = ['U', 'R', 'F', 'D', 'L', 'B'];
+const POWER_NAMES: [&str; 3] = ["", "2", "'"];
+
+const TABLES_FILE: &str = "two_phase_tables.bin";
+const TABLES_MAGIC: &[u8] = b"terminal_cubing two-phase 1\n";
+const UNVISITED: u8 = u8::MAX;
+
+/*
+The coordinates the search works with
+*/
+impl CubieCube {
+    fn twist(&self) -> usize {
+        self.co[..7]
+            .iter()
+            .fold(0, |twist, &co| twist * 3 + co as usize)
+    }
+
+    fn set_twist(&mut self, mut twist: usize) {
+        let mut total = 0;
+        for i in (0..7).rev() {
+            self.co[i] = (twist % 3) as u8;
+            total += self.co[i];
+            twist /= 3;
+        }
+        // The orientation of the last corner follows from the others
+        self.co[7] = (3 - total % 3) % 3;
+    }
+
+    fn flip(&self) -> usize {
+        self.eo[..11]
+            .iter()
+            .fold(0, |flip, &eo| flip * 2 + eo as usize)
+    }
+
+    fn set_flip(&mut self, mut flip: usize) {
+        let mut total = 0;
+        for i in (0..11).rev() {
+            self.eo[i] = (flip % 2) as u8;
+            total += self.eo[i];
+            flip /= 2;
+        }
+        self.eo[11] = total % 2;
+    }
+
+    /*
+    Which positions hold the middle slice edges (FR, FL, BL and BR), ignoring their order
+    */
+    fn slice(&self) -> usize {
+        let positions: Vec<usize> = (0..12).filter(|&i| self.ep[i] >= 8).collect();
+        combination_to_index(&positions)
+    }
+
+    fn set_slice(&mut self, slice: usize) {
+        let positions = index_to_combination(slice, 4, 12);
+        let mut slice_edges = 8..12;
+        let mut other_edges = 0..8;
+        for i in 0..12 {
+            self.ep[i] = if positions.contains(&i) {
+                slice_edges.next()
+            } else {
+                other_edges.next()
+            }
+            .unwrap_or_default();
+        }
+    }
+
+    fn corner_perm(&self) -> usize {
+        permutation_to_index(&self.cp)
+    }
+
+    fn set_corner_perm(&mut self, corner_perm: usize) {
+        self.cp
+            .copy_from_slice(&index_to_permutation(corner_perm, 8));
+    }
+
+    /*
+    Only valid in phase 2, where the U and D edges are in the U and D faces
+    */
+    fn edge_perm(&self) -> usize {
+        permutation_to_index(&self.ep[..8])
+    }
+
+    fn set_edge_perm(&mut self, edge_perm: usize) {
+        self.ep[..8].copy_from_slice(&index_to_permutation(edge_perm, 8));
+    }
+
+    /*
+    Only valid in phase 2, where the middle slice edges are in the middle slice
+    */
+    fn slice_perm(&self) -> usize {
+        let slice_edges: Vec<u8> = self.ep[8..].iter().map(|&edge| edge - 8).collect();
+        permutation_to_index(&slice_edges)
+    }
+
+    fn set_slice_perm(&mut self, slice_perm: usize) {
+        for (i, edge) in index_to_permutation(slice_perm, 4).into_iter().enumerate() {
+            self.ep[8 + i] = edge + 8;
+        }
+    }
+}
+
+fn move_cubes() -> Vec<CubieCube> {
+    let mut cubes = Vec::with_capacity(N_MOVES);
+    for face_turn in &FACE_TURNS {
+        let mut cube = CubieCube::SOLVED;
+        for _ in 0..3 {
+            cube = cube.multiply(face_turn);
+            cubes.push(cube);
+        }
+    }
+    cubes
+}
+
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    (0..k).fold(1, |result, i| result * (n - i) / (i + 1))
+}
+
+/*
+The rank of a sorted combination in the combinatorial number system
+*/
+fn combination_to_index(positions: &[usize]) -> usize {
+    positions
+        .iter()
+        .enumerate()
+        .map(|(i, &position)| binomial(position, i + 1))
+        .sum()
+}
+
+fn index_to_combination(mut index: usize, k: usize, n: usize) -> Vec<usize> {
+    let mut positions = vec![0; k];
+    let mut position = n;
+    for i in (0..k).rev() {
+        position -= 1;
+        while binomial(position, i + 1) > index {
+            position -= 1;
+        }
+        positions[i] = position;
+        index -= binomial(position, i + 1);
+    }
+    positions
+}
+
+/*
+The rank of a permutation of 0..n in lexicographic order
+*/
+fn permutation_to_index(permutation: &[u8]) -> usize {
+    let n = permutation.len();
+    (0..n).fold(0, |index, i| {
+        let smaller_after = permutation[i + 1..]
+            .iter()
+            .filter(|&&later| later < permutation[i])
+            .count();
+        index * (n - i) + smaller_after
+    })
+}
+
+fn index_to_permutation(mut index: usize, n: usize) -> Vec<u8> {
+    let mut digits = vec![0; n];
+    for i in (0..n).rev() {
+        digits[i] = index % (n - i);
+        index /= n - i;
+    }
+    let mut remaining: Vec<u8> = (0..n as u8).collect();
+    digits
+        .into_iter()
+        .map(|digit| remaining.remove(digit))
+        .collect()
+}
+
+fn parity(permutation: &[u8]) -> bool {
+    let mut inversions = 0;
+    for i in 0..permutation.len() {
+        for j in i + 1..permutation.len() {
+            if permutation[i] > permutation[j] {
+                inversions += 1;
+            }
+        }
+    }
+    inversions % 2 == 1
+}
+
+/*
+Move and pruning tables for the search, the pruning tables take a while to build so they are cached on disk
+*/
+pub struct Tables {
+    twist_move: Vec<u16>,
+    flip_move: Vec<u16>,
+    slice_move: Vec<u16>,
+    corner_perm_move: Vec<u16>,
+    edge_perm_move: Vec<u16>,
+    slice_perm_move: Vec<u16>,
+    // How many moves each pair of coordinates is from being solved at least
+    slice_twist_prune: Vec<u8>,
+    slice_flip_prune: Vec<u8>,
+    slice_perm_corner_prune: Vec<u8>,
+    slice_perm_edge_prune: Vec<u8>,
+}
+
+impl Tables {
+    pub fn load() -> Tables {
+        let all_moves: Vec<usize> = (0..N_MOVES).collect();
+        let twist_move = move_table(N_TWIST, CubieCube::set_twist, CubieCube::twist, &all_moves);
+        let flip_move = move_table(N_FLIP, CubieCube::set_flip, CubieCube::flip, &all_moves);
+        let slice_move = move_table(N_SLICE, CubieCube::set_slice, CubieCube::slice, &all_moves);
+        let corner_perm_move = move_table(
+            N_CORNER_PERM,
+            CubieCube::set_corner_perm,
+            CubieCube::corner_perm,
+            &PHASE_2_MOVES,
+        );
+        let edge_perm_move = move_table(
+            N_EDGE_PERM,
+            CubieCube::set_edge_perm,
+            CubieCube::edge_perm,
+            &PHASE_2_MOVES,
+        );
+        let slice_perm_move = move_table(
+            N_SLICE_PERM,
+            CubieCube::set_slice_perm,
+            CubieCube::slice_perm,
+            &PHASE_2_MOVES,
+        );
+
+        let mut tables = Tables {
+            twist_move,
+            flip_move,
+            slice_move,
+            corner_perm_move,
+            edge_perm_move,
+            slice_perm_move,
+            slice_twist_prune: Vec::new(),
+            slice_flip_prune: Vec::new(),
+            slice_perm_corner_prune: Vec::new(),
+            slice_perm_edge_prune: Vec::new(),
+        };
+        if tables.read_prune_tables().is_err() {
+            tables.build_prune_tables();
+            // Not being able to cache them only makes the next start slower
+            let _ = tables.write_prune_tables();
+        }
+        tables
+    }
+
+    fn build_prune_tables(&mut self) {
+        let all_moves: Vec<usize> = (0..N_MOVES).collect();
+        self.slice_twist_prune = prune_table(
+            (&self.slice_move, N_SLICE, CubieCube::SOLVED.slice()),
+            (&self.twist_move, N_TWIST, CubieCube::SOLVED.twist()),
+            &all_moves,
+        );
+        self.slice_flip_prune = prune_table(
+            (&self.slice_move, N_SLICE, CubieCube::SOLVED.slice()),
+            (&self.flip_move, N_FLIP, CubieCube::SOLVED.flip()),
+            &all_moves,
+        );
+        self.slice_perm_corner_prune = prune_table(
+            (
+                &self.slice_perm_move,
+                N_SLICE_PERM,
+                CubieCube::SOLVED.slice_perm(),
+            ),
+            (
+                &self.corner_perm_move,
+                N_CORNER_PERM,
+                CubieCube::SOLVED.corner_perm(),
+            ),
+            &PHASE_2_MOVES,
+        );
+        self.slice_perm_edge_prune = prune_table(
+            (
+                &self.slice_perm_move,
+                N_SLICE_PERM,
+                CubieCube::SOLVED.slice_perm(),
+            ),
+            (
+                &self.edge_perm_move,
+                N_EDGE_PERM,
+                CubieCube::SOLVED.edge_perm(),
+            ),
+            &PHASE_2_MOVES,
+        );
+    }
+
+    fn read_prune_tables(&mut self) -> io::Result<()> {
+        let contents = fs::read(storage::cache_dir()?.join(TABLES_FILE))?;
+        let sizes = [
+            N_SLICE * N_TWIST,
+            N_SLICE * N_FLIP,
+            N_SLICE_PERM * N_CORNER_PERM,
+            N_SLICE_PERM * N_EDGE_PERM,
+        ];
+        let tables = contents
+            .strip_prefix(TABLES_MAGIC)
+            .filter(|tables| tables.len() == sizes.iter().sum::<usize>());
+        let mut tables = match tables {
+            Some(tables) => tables,
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "The cached two-phase tables are invalid",
+                ))
+            }
+        };
+        let mut next_table = |size: usize| {
+            let (table, rest) = tables.split_at(size);
+            tables = rest;
+            table.to_vec()
+        };
+        self.slice_twist_prune = next_table(sizes[0]);
+        self.slice_flip_prune = next_table(sizes[1]);
+        self.slice_perm_corner_prune = next_table(sizes[2]);
+        self.slice_perm_edge_prune = next_table(sizes[3]);
+        Ok(())
+    }
+
+    fn write_prune_tables(&self) -> io::Result<()> {
+        let dir = storage::cache_dir()?;
+        fs::create_dir_all(&dir)?;
+        let contents = [
+            TABLES_MAGIC,
+            &self.slice_twist_prune,
+            &self.slice_flip_prune,
+            &self.slice_perm_corner_prune,
+            &self.slice_perm_edge_prune,
+        ]
+        .concat();
+        storage::write_atomic(&dir.join(TABLES_FILE), &contents)
+    }
+}
+
+/*
+For each coordinate value, the value after each move (moves that are not given are left as 0)
+*/
+fn move_table(
+    size: usize,
+    set: fn(&mut CubieCube, usize),
+    get: fn(&CubieCube) -> usize,
+    moves: &[usize],
+) -> Vec<u16> {
+    let move_cubes = move_cubes();
+    let mut table = vec![0; size * N_MOVES];
+    let mut cube = CubieCube::SOLVED;
+    for coordinate in 0..size {
+        set(&mut cube, coordinate);
+        for &m in moves {
+            table[coordinate * N_MOVES + m] = get(&cube.multiply(&move_cubes[m])) as u16;
+        }
+    }
+    table
+}
+
+/*
+A breadth-first search from the solved state over pairs of coordinates
+*/
+fn prune_table(
+    (move_table_1, size_1, solved_1): (&[u16], usize, usize),
+    (move_table_2, size_2, solved_2): (&[u16], usize, usize),
+    moves: &[usize],
+) -> Vec<u8> {
+    let mut table = vec![UNVISITED; size_1 * size_2];
+    let solved = solved_1 * size_2 + solved_2;
+    table[solved] = 0;
+    let mut queue = VecDeque::from([solved]);
+    while let Some(index) = queue.pop_front() {
+        let (coordinate_1, coordinate_2) = (index / size_2, index % size_2);
+        for &m in moves {
+            let next = move_table_1[coordinate_1 * N_MOVES + m] as usize * size_2
+                + move_table_2[coordinate_2 * N_MOVES + m] as usize;
+            if table[next] == UNVISITED {
+                table[next] = table[index] + 1;
+                queue.push_back(next);
+            }
+        }
+    }
+    table
+}
+
+struct Search<'a> {
+    tables: &'a Tables,
+    cube: CubieCube,
+    move_cubes: Vec<CubieCube>,
+    moves: Vec<usize>,
+}
+
+impl Search<'_> {
+    fn phase_1(&mut self, twist: usize, flip: usize, slice: usize, togo: usize) -> bool {
+        if togo == 0 {
+            if self.phase_1_distance(twist, flip, slice) != 0 {
+                return false;
+            }
+            // Ending on a phase 2 move means a shorter phase 1 was already tried
+            if let Some(&last) = self.moves.last() {
+                if PHASE_2_MOVES.contains(&last) {
+                    return false;
+                }
+            }
+            return self.start_phase_2();
+        }
+        for m in 0..N_MOVES {
+            if self.is_redundant(m) {
+                continue;
+            }
+            let twist = self.tables.twist_move[twist * N_MOVES + m] as usize;
+            let flip = self.tables.flip_move[flip * N_MOVES + m] as usize;
+            let slice = self.tables.slice_move[slice * N_MOVES + m] as usize;
+            if self.phase_1_distance(twist, flip, slice) >= togo {
+                continue;
+            }
+            self.moves.push(m);
+            if self.phase_1(twist, flip, slice, togo - 1) {
+                return true;
+            }
+            self.moves.pop();
+        }
+        false
+    }
+
+    fn start_phase_2(&mut self) -> bool {
+        let cube = self
+            .moves
+            .iter()
+            .fold(self.cube, |cube, &m| cube.multiply(&self.move_cubes[m]));
+        let (corner_perm, edge_perm, slice_perm) =
+            (cube.corner_perm(), cube.edge_perm(), cube.slice_perm());
+        let max_depth = MAX_LENGTH - self.moves.len();
+        (0..=max_depth).any(|depth| self.phase_2(corner_perm, edge_perm, slice_perm, depth))
+    }
+
+    fn phase_2(
+        &mut self,
+        corner_perm: usize,
+        edge_perm: usize,
+        slice_perm: usize,
+        togo: usize,
+    ) -> bool {
+        if togo == 0 {
+            return corner_perm == CubieCube::SOLVED.corner_perm()
+                && edge_perm == CubieCube::SOLVED.edge_perm()
+                && slice_perm == CubieCube::SOLVED.slice_perm();
+        }
+        for m in PHASE_2_MOVES {
+            if self.is_redundant(m) {
+                continue;
+            }
+            let corner_perm = self.tables.corner_perm_move[corner_perm * N_MOVES + m] as usize;
+            let edge_perm = self.tables.edge_perm_move[edge_perm * N_MOVES + m] as usize;
+            let slice_perm = self.tables.slice_perm_move[slice_perm * N_MOVES + m] as usize;
+            if self.phase_2_distance(corner_perm, edge_perm, slice_perm) >= togo {
+                continue;
+            }
+            self.moves.push(m);
+            if self.phase_2(corner_perm, edge_perm, slice_perm, togo - 1) {
+                return true;
+            }
+            self.moves.pop();
+        }
+        false
+    }
+
+    fn phase_1_distance(&self, twist: usize, flip: usize, slice: usize) -> usize {
+        let tables = self.tables;
+        tables.slice_twist_prune[slice * N_TWIST + twist]
+            .max(tables.slice_flip_prune[slice * N_FLIP + flip]) as usize
+    }
+
+    fn phase_2_distance(&self, corner_perm: usize, edge_perm: usize, slice_perm: usize) -> usize {
+        let tables = self.tables;
+        tables.slice_perm_corner_prune[slice_perm * N_CORNER_PERM + corner_perm]
+            .max(tables.slice_perm_edge_prune[slice_perm * N_EDGE_PERM + edge_perm])
+            as usize
+    }
+
+    /*
+    Turning the same face twice in a row, or opposite faces in both orders, gives the same positions more than once
+    */
+    fn is_redundant(&self, m: usize) -> bool {
+        let face = m / 3;
+        match self.moves.last() {
+            Some(&last) => last / 3 == face || last / 3 == face + 3,
+            None => false,
+        }
+    }
+}
+
+fn solve(tables: &Tables, cube: CubieCube) -> Vec<usize> {
+    let mut search = Search {
+        tables,
+        cube,
+        move_cubes: move_cubes(),
+        moves: Vec::new(),
+    };
+    let (twist, flip, slice) = (cube.twist(), cube.flip(), cube.slice());
+    for depth in 0..=MAX_LENGTH {
+        if search.phase_1(twist, flip, slice, depth) {
+            break;
+        }
+    }
+    search.moves
+}
+
+/*
+A scramble in WCA notation that brings a solved cube into a uniformly random state
+*/
+pub fn random_state_scramble(tables: &Tables) -> String {
+    let mut rng = rand::thread_rng();
+    let mut cube = CubieCube::SOLVED;
+    cube.set_corner_perm(rng.gen_range(0..N_CORNER_PERM));
+    cube.ep
+        .copy_from_slice(&index_to_permutation(rng.gen_range(0..479001600), 12));
+    // Swapping two edges fixes the parity if the corners and edges do not match
+    if parity(&cube.cp) != parity(&cube.ep) {
+        cube.ep.swap(0, 1);
+    }
+    cube.set_twist(rng.gen_range(0..N_TWIST));
+    cube.set_flip(rng.gen_range(0..N_FLIP));
+
+    let solution = solve(tables, cube);
+    // Undoing the solution scrambles a solved cube into this state
+    let scramble = solution
+        .iter()
+        .rev()
+        .map(|&m| format!("{}{}", FACE_NAMES[m / 3], POWER_NAMES[2 - m % 3]))
+        .collect::<Vec<String>>()
+        .join(" ");
+    debug_assert!(is_valid_scramble(&scramble, &solution, &cube));
+    scramble
+}
+
+/*
+Checks the scramble against the facelet level model
+*/
+fn is_valid_scramble(scramble: &str, solution: &[usize], cube: &CubieCube) -> bool {
+    let mut facelets = FaceletCube::solved(3);
+    if facelets.apply_algorithm(scramble).is_err()
+        || facelets.to_cubie() != Some(*cube)
+        || FaceletCube::from(cube) != facelets
+    {
+        return false;
+    }
+    let solution = solution
+        .iter()
+        .map(|&m| format!("{}{}", FACE_NAMES[m / 3], POWER_NAMES[m % 3]))
+        .collect::<Vec<String>>()
+        .join(" ");
+    facelets.apply_algorithm(&solution).is_ok() && facelets.is_solved()
+}
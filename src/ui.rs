@@ -2,145 +2,507 @@
 Basically the HTML/CSS of the program
 */
 
-use crate::App;
+use crate::{
+    big_digits,
+    config::Setting,
+    cube::{Face, FaceletCube},
+    format, layout,
+    solve::Penalty,
+    timer::TimerStatus,
+    App, Detail, Mode,
+};
+use std::time::{Duration, Instant};
 use tui::{
     backend::Backend,
-    layout::{Constraint, Corner, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Span, Spans},
-    widgets::{Block, Borders, List, ListItem},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
     Frame,
 };
 
 pub fn draw<B: Backend>(f: &mut Frame<B>, app: &mut App) {
-    // Split screen horizontally into three chunks
-    let chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-                Constraint::Percentage(30),
-                Constraint::Percentage(50),
-                Constraint::Percentage(20),
-        ]
-        .as_ref())
-        .split(f.size());
-
-    draw_left_section(f, app, chunks[0]);
-    draw_central_timer(f, app, chunks[1]);
-    draw_keybind_help(f, app, chunks[2]);
+    // The theme's colours under everything drawn on top
+    f.render_widget(Block::default().style(app.theme.text()), f.size());
+    let screen = layout::split(f.size());
+    draw_central_timer(f, app, screen.timer);
+    if let Some(times) = screen.times {
+        draw_times(f, app, times);
+    }
+    if let Some(preview) = screen.preview {
+        draw_scramble_preview(f, app, preview);
+    }
+    if let Some(footer) = screen.footer {
+        draw_keybind_help(f, app, footer);
+    }
+    match app.mode {
+        Mode::Timer | Mode::ManualEntry { .. } => {}
+        Mode::Settings { .. } => draw_settings(f, app),
+        Mode::SolveDetails { index, .. } => draw_solve_details(f, app, index),
+        _ => draw_sessions(f, app),
+    }
 }
 
-fn draw_left_section<B: Backend>(f: &mut Frame<B>, app: &mut App, main_chunk: Rect) {
-    let chunks = Layout::default()
-        .constraints([
-            Constraint::Percentage(50), 
-            Constraint::Percentage(50),
-        ]
-        .as_ref())
-        .direction(Direction::Vertical)
-        .split(main_chunk);
-    // Iterate through all elements in the `items` app and append some debug text to it.
+fn draw_times<B: Backend>(f: &mut Frame<B>, app: &mut App, main_chunk: Rect) {
+    // Iterate through all solves and add the ao5 and ao12 ending at each as columns
     let items: Vec<ListItem> = app
         .times
         .items
         .iter()
-        .map(|i| {
-            let lines = vec![Spans::from(i.to_string())];
-            ListItem::new(lines).style(Style::default().fg(Color::Black).bg(Color::White))
+        .zip(&app.stats.rolling)
+        .enumerate()
+        .map(|(index, (i, (ao5, ao12)))| {
+            let lines = vec![Spans::from(format!(
+                "{:>4}. {:>9} {:>9} {:>9}",
+                index + 1,
+                app.time_format.solve(i.time, i.penalty),
+                app.time_format.average(*ao5),
+                app.time_format.average(*ao12),
+            ))];
+            ListItem::new(lines).style(app.theme.list())
         })
         .collect();
 
     // Create a List from all list items and highlight the currently selected one
     let items = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title("List"))
-        .highlight_style(
-            Style::default()
-                .bg(Color::LightGreen)
-                .add_modifier(Modifier::BOLD),
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("{} (time, ao5, ao12)", app.session().name)),
         )
+        .highlight_style(app.theme.highlight())
         .highlight_symbol(">> ");
 
     // We can now render the item list
-    f.render_stateful_widget(items, chunks[1], &mut app.times.state);
-
-    let text = vec![
-        Spans::from(Span::styled(
-        "       _____",Style::default())),Spans::from(Span::styled(
-        "    __/  |  \\__", Style::default())),Spans::from(Span::styled(
-        "   /     |     \\", Style::default())),Spans::from(Span::styled(
-        "  /      |   /  \\", Style::default())),Spans::from(Span::styled(
-        " |       | /     |", Style::default())),Spans::from(Span::styled(
-        "|        O        |", Style::default())),Spans::from(Span::styled(
-        " |   ___    _    |", Style::default())),Spans::from(Span::styled(
-        " \\   |    /    /", Style::default())),Spans::from(Span::styled(
-        "  \\  |    \\_  /", Style::default())),Spans::from(Span::styled(
-        "   \\_________/", Style::default()))
+    f.render_stateful_widget(items, main_chunk, &mut app.times.state);
+}
+
+/*
+An unfolded net of the cube after the scramble, with the stickers as large as fit in the chunk
+*/
+fn draw_scramble_preview<B: Backend>(f: &mut Frame<B>, app: &App, main_chunk: Rect) {
+    let title = format!("Scramble ({})", app.session().puzzle.name());
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let area = block.inner(main_chunk);
+    f.render_widget(block, main_chunk);
+
+    // Only the cubes have a preview
+    let Some(size) = app.session().puzzle.cube_size() else {
+        return;
+    };
+    let mut cube = FaceletCube::solved(size);
+    if cube.apply_algorithm(&app.scramble).is_err() {
+        return;
+    }
+    let size = cube.size() as u16;
+    // Terminal cells are about twice as tall as they are wide
+    let sticker_height = (area.height / (3 * size)).min(area.width / (8 * size));
+    let (sticker_width, sticker_height) = match sticker_height {
+        0 if area.width >= 4 * size && area.height >= 3 * size => (1, 1),
+        0 => return,
+        height => (2 * height, height),
+    };
+
+    // Which face is in each row and column of faces in the net
+    let net = [
+        [None, Some(Face::U), None, None],
+        [Some(Face::L), Some(Face::F), Some(Face::R), Some(Face::B)],
+        [None, Some(Face::D), None, None],
     ];
-    let time_text = tui::widgets::Paragraph::new(text)
-        .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().fg(Color::White).bg(Color::Black))
-        .alignment(tui::layout::Alignment::Center)
-        .wrap(tui::widgets::Wrap { trim: true });
-    f.render_widget(time_text, chunks[0]);
+    let sticker = " ".repeat(sticker_width as usize);
+    let blank = " ".repeat((sticker_width * size) as usize);
+    let mut text = Vec::new();
+    for faces in &net {
+        for row in 0..size as usize {
+            let mut spans = Vec::new();
+            for face in faces {
+                match face {
+                    Some(face) => spans.extend((0..size as usize).map(|col| {
+                        let colour = sticker_colour(cube.facelet(*face, row, col));
+                        Span::styled(sticker.clone(), Style::default().bg(colour))
+                    })),
+                    None => spans.push(Span::raw(blank.clone())),
+                }
+            }
+            for _ in 0..sticker_height {
+                text.push(Spans::from(spans.clone()));
+            }
+        }
+    }
+    let net_height = 3 * size * sticker_height;
+    let preview_area = Rect {
+        y: area.y + (area.height - net_height) / 2,
+        height: net_height,
+        ..area
+    };
+    let preview = tui::widgets::Paragraph::new(text).alignment(tui::layout::Alignment::Center);
+    f.render_widget(preview, preview_area);
+}
+
+fn sticker_colour(face: Face) -> Color {
+    match face {
+        Face::U => Color::White,
+        Face::R => Color::Red,
+        Face::F => Color::Green,
+        Face::D => Color::Yellow,
+        Face::L => Color::Rgb(255, 128, 0),
+        Face::B => Color::Blue,
+    }
 }
 
 fn draw_central_timer<B: Backend>(f: &mut Frame<B>, app: &mut App, main_chunk: Rect) {
     // This is the central timer section
-    let mut centeral_time = app.time.to_string();
-    match centeral_time.len() {
-        0 => centeral_time = "0.00".to_owned(),
-        1 => centeral_time = "0.0".to_owned() + &centeral_time,
-        2 => centeral_time = "0.".to_owned() + &centeral_time,
-        _ => {
-            centeral_time.insert(app.time.to_string().len() - 2, '.');
-        }
+    let now = Instant::now();
+    let milliseconds = app.timer.time_ms(now);
+    let inspecting = matches!(
+        app.timer.status,
+        TimerStatus::Countdown | TimerStatus::Holding | TimerStatus::Ready
+    );
+    let centeral_time = if let Mode::ManualEntry { text, invalid } = &app.mode {
+        let hint = if *invalid { " (not a time)" } else { "" };
+        format!("Time: {text}_{hint}")
+    } else if !inspecting {
+        let time = Duration::from_millis(milliseconds as u64);
+        app.time_format.solve(time, app.timer.penalty())
+    } else if milliseconds < 0 {
+        // Past the end of inspection
+        "+2".to_owned()
+    } else {
+        app.time_format.milliseconds(milliseconds)
+    };
+    // Like a stackmat, red while the key is held and green once releasing it starts the solve
+    let time_style = match app.timer.status {
+        TimerStatus::Holding => app.theme.holding(),
+        TimerStatus::Ready => app.theme.ready(),
+        _ => app.theme.text(),
+    };
+    let area = main_chunk;
+    // The border is dropped on tiny terminals to leave room for the time
+    let borders = if area.height >= 7 {
+        Borders::ALL
+    } else {
+        Borders::NONE
+    };
+    let block = Block::default().borders(borders).style(app.theme.text());
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let scramble = match app.scramble.as_str() {
+        "" => "Generating scramble...",
+        scramble => scramble,
+    };
+    let mut below = Vec::new();
+    if let Some(message) = &app.message {
+        below.push(Spans::from(Span::styled(
+            message.clone(),
+            app.theme.warning(),
+        )));
+    }
+    if let Some(seconds) = app.timer.inspection_warning(now) {
+        below.push(Spans::from(Span::styled(
+            format!("{} seconds!", seconds),
+            app.theme.warning(),
+        )));
+    }
+    // The phases finished so far in a split solve
+    if app.timer.status == TimerStatus::Countup && !app.timer.splits().is_empty() {
+        let mut start = Duration::ZERO;
+        let times: Vec<Duration> = app
+            .timer
+            .splits()
+            .iter()
+            .map(|&end| {
+                let time = end - start;
+                start = end;
+                time
+            })
+            .collect();
+        below.push(Spans::from(phases_text(app, &times)));
+    }
+    // Enough lines for the scramble once it wraps, with one to spare as it wraps between moves
+    let scramble_height = (scramble.len() as u16 / inner.width.max(1) + 2)
+        .min(inner.height.saturating_sub(below.len() as u16 + 1))
+        .max(1);
+    // The statistics of the session under the time, if there is room left for the time
+    let mut stats = vec![Spans::from("")];
+    for (name, average) in &app.stats.current {
+        stats.push(Spans::from(Span::styled(
+            format!("{}: {}", name, app.time_format.average(*average)),
+            app.theme.stats(),
+        )));
     }
-    let text = vec![
-        Spans::from(Span::styled(
+    if !app.stats.phases.is_empty() {
+        stats.push(Spans::from(Span::styled(
+            format!("Phases over {} solves:", app.stats.phase_solves),
+            app.theme.stats(),
+        )));
+        stats.push(Spans::from(Span::styled(
+            phases_text(app, &app.stats.phases),
+            app.theme.stats(),
+        )));
+    }
+    if inner.height >= scramble_height + below.len() as u16 + stats.len() as u16 + 5 {
+        below.extend(stats);
+    }
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(scramble_height),
+                Constraint::Min(1),
+                Constraint::Length(below.len() as u16),
+            ]
+            .as_ref(),
+        )
+        .split(inner);
+
+    let scramble = Paragraph::new(Span::styled(scramble, app.theme.scramble()))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    f.render_widget(scramble, chunks[0]);
+
+    // Typing a time is shown as text, otherwise the time is as big as fits
+    let big_time = match app.mode {
+        Mode::ManualEntry { .. } => None,
+        _ => big_digits::render(&centeral_time, chunks[1].width, chunks[1].height),
+    };
+    let time_lines: Vec<Spans> = match big_time {
+        Some(rows) => rows
+            .into_iter()
+            .map(|row| Spans::from(Span::styled(row, time_style)))
+            .collect(),
+        None => vec![Spans::from(Span::styled(
             centeral_time,
-            Style::default().add_modifier(Modifier::ITALIC),
-        )),
-        Spans::from(Span::styled("Second line", Style::default().fg(Color::Red))),
-    ];
-    let time_text = tui::widgets::Paragraph::new(text)
-        .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().fg(Color::White).bg(Color::Black))
-        .alignment(tui::layout::Alignment::Center)
-        .wrap(tui::widgets::Wrap { trim: true });
-    let timer_margin = tui::layout::Margin {
-        vertical: 10,
-        horizontal: 30,
-    };
-    main_chunk.inner(&timer_margin);
-    f.render_widget(time_text, main_chunk.inner(&timer_margin));
+            time_style.add_modifier(Modifier::ITALIC),
+        ))],
+    };
+    let padding = chunks[1].height.saturating_sub(time_lines.len() as u16) / 2;
+    let time_area = Rect {
+        y: chunks[1].y + padding,
+        height: chunks[1].height - padding,
+        ..chunks[1]
+    };
+    let time = Paragraph::new(time_lines).alignment(Alignment::Center);
+    f.render_widget(time, time_area);
+
+    let below = Paragraph::new(below).alignment(Alignment::Center);
+    f.render_widget(below, chunks[2]);
 }
 
 fn draw_keybind_help<B: Backend>(f: &mut Frame<B>, app: &mut App, main_chunk: Rect) {
-    let keybinds: Vec<ListItem> = app
-        .keybinds
+    let mut spans = Vec::new();
+    for (action, keys) in app.keymap.help() {
+        spans.push(Span::styled(keys, app.theme.key()));
+        spans.push(Span::raw(format!(" {}  ", action)));
+    }
+    f.render_widget(Paragraph::new(Spans::from(spans)), main_chunk);
+}
+
+/*
+The list of sessions over the middle of the screen, with the prompt for whatever is being done to the selected one
+*/
+fn draw_sessions<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+    let area = centred(f.size(), 60, 60);
+    f.render_widget(Clear, area);
+    f.render_widget(Block::default().style(app.theme.text()), area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(4)].as_ref())
+        .split(area);
+
+    let items: Vec<ListItem> = app
+        .sessions
+        .sessions
         .iter()
-        .rev()
-        .map(|&(event, level)| {
-            // Colorcode the level depending on its type
-            let s = match level {
-                "CRITICAL" => Style::default().fg(Color::Red),
-                "ERROR" => Style::default().fg(Color::Magenta),
-                "WARNING" => Style::default().fg(Color::Yellow),
-                "INFO" => Style::default().fg(Color::Blue),
-                _ => Style::default(),
+        .enumerate()
+        .map(|(index, session)| {
+            // The open session's solves are in the times list
+            let solves = if index == app.sessions.active {
+                app.times.items.len()
+            } else {
+                session.solves.len()
+            };
+            let open = if index == app.sessions.active {
+                "*"
+            } else {
+                " "
             };
-            // Add a example datetime and apply proper spacing between them
-            let header = Spans::from(vec![
-                Span::styled(format!("{:<9}", level), s),
-                Span::raw(" "),
-                Span::styled(event, Style::default().add_modifier(Modifier::ITALIC)),
-            ]);
-            // Add the line to list
-            ListItem::new(header)
+            ListItem::new(format!(
+                "{open} {} ({}, {} solves)",
+                session.name,
+                session.puzzle.name(),
+                solves
+            ))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("Sessions"))
+        .highlight_style(app.theme.highlight())
+        .highlight_symbol(">> ");
+    f.render_stateful_widget(list, chunks[0], &mut app.session_list);
+
+    let prompt = match &app.mode {
+        Mode::Prompt { prompt, text } => format!("{}: {text}_", prompt.label()),
+        Mode::ChooseFormat { .. } => "c csTimer, t Twisty Timer, d CubeDesk".to_string(),
+        Mode::ConfirmDelete => "Delete this session and all its solves? (y/n)".to_string(),
+        _ => app.session_message.clone().unwrap_or_else(|| {
+            "Enter open, n new, r rename, d delete, m merge into open, p puzzle, \
+            i import, e export, Esc close"
+                .to_string()
+        }),
+    };
+    let prompt = Paragraph::new(prompt)
+        .block(Block::default().borders(Borders::ALL))
+        .wrap(Wrap { trim: true });
+    f.render_widget(prompt, chunks[1]);
+}
+
+/*
+Every setting with its value, and what is being typed while one is changed
+*/
+fn draw_settings<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+    let area = centred(f.size(), 60, 60);
+    f.render_widget(Clear, area);
+    f.render_widget(Block::default().style(app.theme.text()), area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(4)].as_ref())
+        .split(area);
+
+    let items: Vec<ListItem> = Setting::ALL
+        .iter()
+        .map(|&setting| {
+            ListItem::new(format!(
+                "{:<24}{}",
+                setting.description(),
+                app.config.value(setting)
+            ))
         })
         .collect();
-    // Border formating
-    let events_list = List::new(keybinds)
-        .block(Block::default().borders(Borders::ALL).title("Keybinds"))
-        .start_corner(Corner::BottomLeft);
-    f.render_widget(events_list, main_chunk);
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("Settings"))
+        .highlight_style(app.theme.highlight())
+        .highlight_symbol(">> ");
+    f.render_stateful_widget(list, chunks[0], &mut app.settings_list);
+
+    let mut prompt = match &app.mode {
+        Mode::Settings {
+            editing: Some(text),
+        } => format!("New value: {text}_"),
+        _ => "Enter change, Esc close".to_string(),
+    };
+    if let Some(message) = &app.settings_message {
+        prompt = format!("{prompt}\n{message}");
+    }
+    let prompt = Paragraph::new(prompt)
+        .block(Block::default().borders(Borders::ALL))
+        .wrap(Wrap { trim: true });
+    f.render_widget(prompt, chunks[1]);
+}
+
+/*
+Everything about one solve from the list of times, and what is being done to it
+*/
+fn draw_solve_details<B: Backend>(f: &mut Frame<B>, app: &App, index: usize) {
+    let (Some(solve), Some(&(ao5, ao12))) =
+        (app.times.items.get(index), app.stats.rolling.get(index))
+    else {
+        return;
+    };
+    let area = centred(f.size(), 60, 60);
+    f.render_widget(Clear, area);
+    f.render_widget(Block::default().style(app.theme.text()), area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(4)].as_ref())
+        .split(area);
+
+    let penalty = match solve.penalty {
+        Penalty::None => "None",
+        Penalty::PlusTwo => "+2",
+        Penalty::Dnf => "DNF",
+    };
+    let comment = match solve.comment.as_str() {
+        "" => "-",
+        comment => comment,
+    };
+    let mut details = vec![
+        ("Time", app.time_format.solve(solve.time, solve.penalty)),
+        ("Penalty", penalty.to_string()),
+        ("ao5", app.time_format.average(ao5)),
+        ("ao12", app.time_format.average(ao12)),
+        ("Date", format::timestamp(solve.timestamp)),
+        ("Scramble", solve.scramble.clone()),
+        ("Comment", comment.to_string()),
+    ];
+    if !solve.splits.is_empty() {
+        details.insert(1, ("Phases", phases_text(app, &solve.phase_times())));
+    }
+    let lines: Vec<Spans> = details
+        .into_iter()
+        .map(|(name, value)| {
+            Spans::from(vec![
+                Span::styled(format!("{name}: "), app.theme.key()),
+                Span::raw(value),
+            ])
+        })
+        .collect();
+    let details = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Solve {}", index + 1)),
+        )
+        .wrap(Wrap { trim: true });
+    f.render_widget(details, chunks[0]);
+
+    let prompt = match &app.mode {
+        Mode::SolveDetails {
+            detail: Detail::Comment(text),
+            ..
+        } => format!("Comment: {text}_"),
+        Mode::SolveDetails {
+            detail: Detail::ConfirmDelete,
+            ..
+        } => "Delete this solve? (y/n)".to_string(),
+        _ => app.solve_message.clone().unwrap_or_else(|| {
+            "2 +2, d DNF, c comment, y copy scramble, x delete, Esc close".to_string()
+        }),
+    };
+    let prompt = Paragraph::new(prompt)
+        .block(Block::default().borders(Borders::ALL))
+        .wrap(Wrap { trim: true });
+    f.render_widget(prompt, chunks[1]);
+}
+
+/*
+The time of each phase after its name, from the method in the config if it has that many phases
+*/
+fn phases_text(app: &App, times: &[Duration]) -> String {
+    let mut names = app.config.phases();
+    if names.len() < times.len() {
+        names = (1..=times.len())
+            .map(|phase| format!("Phase {phase}"))
+            .collect();
+    }
+    names
+        .iter()
+        .zip(times)
+        .map(|(name, &time)| format!("{name} {}", app.time_format.time(time)))
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+/*
+A rectangle in the middle of the area, with its size as percentages of the area
+*/
+fn centred(area: Rect, width: u16, height: u16) -> Rect {
+    let width = area.width * width / 100;
+    let height = area.height * height / 100;
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
 }